@@ -7,7 +7,7 @@ use crate::{
         attributes_stats::{CharacterStatBundle, EquipmentStats, ProjectileStats},
         characters::{
             ai::components::AiType,
-            components::{CharacterMoveState, CharacterType},
+            components::{CharacterMoveState, CharacterType, HazardContacts},
         },
         components::{ActorColliderType, TimeToLive},
         items::weapons::components::{AttackDamage, WeaponDescriptor, WeaponHolder},
@@ -36,6 +36,8 @@ pub struct CharacterBundle {
     pub identifier: RegistryIdentifier,
     /// actors current movement data
     pub move_state: CharacterMoveState,
+    /// hazard tiles actor is touching
+    pub hazards: HazardContacts,
     /// actor type
     pub actor_type: CharacterType,
     /// actor stat
@@ -95,11 +95,32 @@ impl AspenCollisionLayer {
         )
     }
 
+    /// hazard tile sensor that only touches actors,
+    /// projectiles fly over hazards instead of hitting them
+    pub fn hazard_tile() -> CollisionLayers {
+        CollisionLayers::new(Self::StaticObject, [Self::DynamicActor])
+    }
+
     pub fn no_collisions() -> CollisionLayers {
         CollisionLayers::new(Self::Empty, LayerMask::NONE)
     }
 }
 
+/// collision layer tests
+#[cfg(test)]
+mod tests {
+    use super::AspenCollisionLayer;
+
+    /// bullets fired over mud, spikes or pits must not be despawned by the hazard sensor
+    #[test]
+    fn projectiles_pass_over_hazard_tiles() {
+        let hazard = AspenCollisionLayer::hazard_tile();
+
+        assert!(!hazard.interacts_with(AspenCollisionLayer::projectile_actor()));
+        assert!(hazard.interacts_with(AspenCollisionLayer::dynamic_actor()));
+    }
+}
+
 // supported resolutions
 // const RESOLUTIONS: [(f32, f32); 28] = [
 //     // Common Resolutions (as mentioned before)
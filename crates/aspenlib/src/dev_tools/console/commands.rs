@@ -33,6 +33,26 @@ pub struct TeleportCharacterCommand {
     pub who: Option<CommandTarget>,
 }
 
+/// prints the current dungeons seed,
+/// sets or clears the seed used for the next dungeon
+#[derive(ConsoleCommand, Parser)]
+#[command(name = "seed")]
+pub struct DungeonSeedCommand {
+    /// seed too use for the next dungeon
+    pub seed: Option<u64>,
+    /// go back too random seeds
+    #[arg(long)]
+    pub clear: bool,
+}
+
+/// exports the current dungeon layout as json and a labeled png map
+#[derive(ConsoleCommand, Parser)]
+#[command(name = "export_layout")]
+pub struct ExportLayoutCommand {
+    /// folder too write layout files into
+    pub folder: Option<String>,
+}
+
 //######## COMMAND ARGS ########//
 /// x,y location for comand
 #[derive(Debug, Clone, Copy)]
@@ -27,6 +27,10 @@ impl Plugin for QuakeConPlugin {
             .add_console_command::<commands::SpawnActorCommand, _>(systems::spawn_command)
             .add_console_command::<commands::TeleportCharacterCommand, _>(
                 systems::teleport_command,
+            )
+            .add_console_command::<commands::DungeonSeedCommand, _>(systems::seed_command)
+            .add_console_command::<commands::ExportLayoutCommand, _>(
+                systems::export_layout_command,
             );
     }
 }
@@ -1,19 +1,30 @@
+use std::path::Path;
+
 use bevy::prelude::*;
 use bevy_console::ConsoleCommand;
 
 use crate::{
     dev_tools::console::{
         commands::{CommandSpawnType, CommandTarget},
-        commands::{SpawnActorCommand, TeleportCharacterCommand},
+        commands::{
+            DungeonSeedCommand, ExportLayoutCommand, SpawnActorCommand, TeleportCharacterCommand,
+        },
     },
     game::{
         characters::{
             components::CharacterMoveState, player::PlayerSelectedHero, EventSpawnCharacter,
         },
-        game_world::components::{ActorTeleportEvent, TpTriggerEffect},
+        game_world::{
+            components::{ActorTeleportEvent, TpTriggerEffect},
+            dungeonator_v2::{
+                components::Dungeon,
+                export::{LayoutExport, DEFAULT_EXPORT_FOLDER},
+                floors::ActiveDungeon,
+            },
+        },
         items::EventSpawnItem,
     },
-    loading::registry::ActorRegistry,
+    loading::{config::GeneralSettings, registry::ActorRegistry},
 };
 
 /// interprets `SpawnCommand` from console and sends `SpawnActorEvent`
@@ -174,3 +185,52 @@ pub fn teleport_command(
         }
     }
 }
+
+/// replies with current dungeon seed, sets or clears configured seed
+pub fn seed_command(
+    dungeon_query: Query<&Dungeon, With<ActiveDungeon>>,
+    mut general_settings: ResMut<GeneralSettings>,
+    mut cmd: ConsoleCommand<DungeonSeedCommand>,
+) {
+    if let Some(Ok(DungeonSeedCommand { seed, clear })) = cmd.take() {
+        match dungeon_query.get_single() {
+            Ok(dungeon) => cmd.reply(format!("current dungeon seed: {}", dungeon.settings.seed)),
+            Err(_) => cmd.reply("no dungeon spawned"),
+        }
+
+        if clear {
+            general_settings.dungeon_seed = None;
+            cmd.reply_ok("next dungeon will use a random seed");
+        } else if let Some(seed) = seed {
+            general_settings.dungeon_seed = Some(seed);
+            cmd.reply_ok(format!("next dungeon will use seed: {seed}"));
+        } else if let Some(seed) = general_settings.dungeon_seed {
+            cmd.reply_ok(format!("configured seed: {seed}"));
+        } else {
+            cmd.reply_ok("no seed configured, dungeons use random seeds");
+        }
+    }
+}
+
+/// writes the current dungeons layout too json and png files
+pub fn export_layout_command(
+    layout_query: Query<&LayoutExport, With<ActiveDungeon>>,
+    mut cmd: ConsoleCommand<ExportLayoutCommand>,
+) {
+    if let Some(Ok(ExportLayoutCommand { folder })) = cmd.take() {
+        let Ok(layout) = layout_query.get_single() else {
+            cmd.reply_failed("no dungeon layout too export");
+            return;
+        };
+
+        let folder = folder.unwrap_or_else(|| DEFAULT_EXPORT_FOLDER.to_string());
+        match layout.write_files(Path::new(&folder)) {
+            Ok([json, image]) => cmd.reply_ok(format!(
+                "exported layout too {} and {}",
+                json.display(),
+                image.display()
+            )),
+            Err(e) => cmd.reply_failed(format!("could not export layout: {e}")),
+        }
+    }
+}
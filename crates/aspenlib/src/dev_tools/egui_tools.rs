@@ -25,7 +25,10 @@ use bevy_inspector_egui::{
 use pretty_type_name::pretty_type_name;
 use std::{marker::PhantomData, sync::Mutex};
 
-use crate::{game::game_world::dungeonator_v2::GeneratorState, AppStage, GameStage};
+use crate::{
+    game::game_world::dungeonator_v2::{room_database::RoomDatabaseReport, GeneratorState},
+    AppStage, GameStage,
+};
 
 use super::DebugConfig;
 
@@ -64,6 +67,19 @@ impl Plugin for EguiToolsPlugin {
                     .and(|res: Res<DebugConfig>| res.enabled && res.show_world_inspector),
             ),
         ));
+
+        // missing required rooms always shows report, dungeons cant be made without them
+        app.add_systems(
+            DebugToolsSchedule,
+            room_report_ui.run_if(
+                resource_exists::<DebugConfig>
+                    .and(resource_exists::<RoomDatabaseReport>)
+                    .and(|res: Res<DebugConfig>, report: Res<RoomDatabaseReport>| {
+                        report.is_fatal()
+                            || (res.enabled && res.show_room_report && !report.issues.is_empty())
+                    }),
+            ),
+        );
     }
 }
 
@@ -131,6 +147,50 @@ fn world_inspector_ui(world: &mut World) {
         });
 }
 
+/// lists every problem found in room levels when the room database was built
+fn room_report_ui(
+    mut egui_context: Query<&mut EguiContext, With<PrimaryWindow>>,
+    report: Res<RoomDatabaseReport>,
+) {
+    let Ok(mut egui_context) = egui_context.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Room Database Report")
+        .default_size(DEFAULT_SIZE)
+        .show(egui_context.get_mut(), |ui| {
+            for category in &report.missing_categories {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!("no {category} rooms, dungeons can not be generated"),
+                );
+            }
+            ui.label(format!(
+                "{} levels skipped, {} problems",
+                report.skipped_levels.len(),
+                report.issues.len()
+            ));
+
+            egui::ScrollArea::both().show(ui, |ui| {
+                egui::Grid::new("room_report_issues")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("level");
+                        ui.strong("field");
+                        ui.strong("reason");
+                        ui.end_row();
+
+                        for issue in &report.issues {
+                            ui.label(&issue.level);
+                            ui.label(&issue.field);
+                            ui.label(&issue.reason);
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+}
+
 /// Display `Entities`, `Resources` and `Assets` using their respective functions inside headers
 pub fn ui_for_world(world: &mut World, ui: &mut egui::Ui) {
     egui::CollapsingHeader::new("Entities")
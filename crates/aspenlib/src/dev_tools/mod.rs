@@ -54,6 +54,8 @@ pub struct DebugConfig {
     pub show_appstate: bool,
     pub show_gamestate: bool,
     pub show_generatorstate: bool,
+    /// show problems found in room levels when the room database is built
+    pub show_room_report: bool,
 }
 
 impl Default for DebugConfig {
@@ -68,6 +70,7 @@ impl Default for DebugConfig {
             show_appstate: false,
             show_gamestate: false,
             show_generatorstate: false,
+            show_room_report: true,
         }
     }
 }
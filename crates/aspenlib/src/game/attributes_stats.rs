@@ -7,13 +7,17 @@ use std::{
 };
 
 use bevy::{
+    app::{App, Plugin, Update},
     ecs::{bundle::Bundle, component::Component, entity::Entity, reflect::ReflectComponent},
     log::warn,
-    prelude::{Added, Changed, Children, HierarchyQueryExt, Parent, Query, With},
+    prelude::{
+        in_state, Added, Changed, Children, Event, EventReader, HierarchyQueryExt,
+        IntoSystemConfigs, Parent, Query, Res, Time, Timer, TimerMode, With,
+    },
     reflect::Reflect,
 };
 
-use crate::consts::TILE_SIZE;
+use crate::{consts::TILE_SIZE, game::combat::elements::ElementalStatus, register_types, AppStage};
 
 /// how many points of health per stamina point
 pub const HP_PER_STAMINA: i32 = 4;
@@ -24,16 +28,57 @@ pub const DAMAGE_PER_STREGNTH: i32 = 4;
 /// how many points of mana per intelligence point
 pub const MANA_PER_INTELLIGENCE: i32 = 4;
 
+/// character stat and effect functionality
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        register_types!(app, [EffectQueue]);
+
+        app.add_event::<ApplyEffect>()
+            .add_event::<RemoveEffect>()
+            .add_systems(
+                Update,
+                (sync_character_stats, apply_effect_events, tick_effects)
+                    .chain()
+                    .run_if(in_state(AppStage::Running)),
+            );
+    }
+}
+
+/// adds `effect` too `target`s `EffectQueue`,
+/// `Instant` effects are added too the targets stats permanently
+#[derive(Debug, Event, Clone)]
+pub struct ApplyEffect {
+    /// character receiving effect
+    pub target: Entity,
+    /// effect too apply
+    pub effect: Effect,
+}
+
+/// removes effects with `id` from `target`s `EffectQueue`
+#[derive(Debug, Event, Clone)]
+pub struct RemoveEffect {
+    /// character losing effect
+    pub target: Entity,
+    /// id of effect too remove
+    pub id: String,
+}
+
 //TODO: figure out if this and hp and buffs and damage should be seperate components or not
 // intuition says that the buff and damage queues should be seperate components;
 /// updates actor stats if equipment on player changes
+#[allow(clippy::type_complexity)]
 pub fn sync_character_stats(
-    mut characters_with_changed_children: Query<(Entity, &mut CharacterStats), Changed<Children>>,
+    mut characters_with_changed_children: Query<
+        (Entity, &mut CharacterStats, &mut EffectQueue),
+        Changed<Children>,
+    >,
     mut added_stats: Query<(Entity, &mut CharacterStats), Added<CharacterStats>>,
     equipment: Query<&EquipmentStats, With<Parent>>,
     children: Query<&Children>,
 ) {
-    for (character, mut stats) in &mut characters_with_changed_children {
+    for (character, mut stats, mut effects) in &mut characters_with_changed_children {
         let equipment_total_attrs: Vec<Attributes> = children
             .iter_descendants(character)
             .filter_map(|f| {
@@ -51,7 +96,7 @@ pub fn sync_character_stats(
             let equipment_attrs_total: Attributes = equipment_total_attrs.into_iter().sum();
 
             stats.equipment = equipment_attrs_total;
-            stats.current = stats.equipment + stats.base;
+            stats.recalculate(&mut effects);
         }
     }
 
@@ -66,6 +111,57 @@ pub fn sync_character_stats(
     }
 }
 
+/// applies and removes effects requested with `ApplyEffect` and `RemoveEffect` events
+pub fn apply_effect_events(
+    mut apply_events: EventReader<ApplyEffect>,
+    mut remove_events: EventReader<RemoveEffect>,
+    mut characters: Query<(&mut CharacterStats, &mut EffectQueue)>,
+) {
+    for ApplyEffect { target, effect } in apply_events.read() {
+        let Ok((mut stats, mut effects)) = characters.get_mut(*target) else {
+            warn!(
+                "effect {} was applied too a character without stats",
+                effect.id
+            );
+            continue;
+        };
+
+        if effect.duration == EffectDuration::Instant {
+            let added = effect.modifier(&stats.unmodified_attrs());
+            stats.added = stats.added + added;
+        } else if !effects.push_effect(effect.clone()) {
+            continue;
+        }
+        stats.recalculate(&mut effects);
+    }
+
+    for RemoveEffect { target, id } in remove_events.read() {
+        let Ok((mut stats, mut effects)) = characters.get_mut(*target) else {
+            continue;
+        };
+
+        if effects.remove_effect(id) {
+            stats.recalculate(&mut effects);
+        }
+    }
+}
+
+/// ticks timed effects and recalculates stats of characters with expired effects
+pub fn tick_effects(
+    time: Res<Time>,
+    mut characters: Query<(&mut CharacterStats, &mut EffectQueue)>,
+) {
+    for (mut stats, mut effects) in &mut characters {
+        if effects.amount == 0 {
+            continue;
+        }
+
+        if effects.tick(time.delta()) {
+            stats.recalculate(&mut effects);
+        }
+    }
+}
+
 /// stats related components for characters
 #[derive(Debug, Bundle, Clone, Reflect)]
 pub struct CharacterStatBundle {
@@ -75,9 +171,10 @@ pub struct CharacterStatBundle {
     effects: EffectQueue,
     /// list of all damage too be applied too character
     damage: DamageQueue,
+    /// status effects from depleted elemental buffers
+    elemental: ElementalStatus,
 }
 
-//TODO: track id of "effect"
 /// modifiers too player stats
 /// can be spell modifiers, curses, pretty much anything
 #[derive(Debug, Component, Clone, Reflect)]
@@ -166,14 +263,9 @@ pub struct Damage {
 #[derive(Debug, Clone, Copy, PartialEq, Default, Reflect, serde::Deserialize, serde::Serialize)]
 pub struct PhysicalDamage(pub f32);
 
-//TODO:
-// create buffer for enemies that is filled up by elemental damage
-// apply unique effects too characters when elemental buffer is filled
-// earth: character should be slowed
-// fire: character should burn for a bit
-// water: idek
-// air: character should randomly get a burst of uncontrollable speed
 /// different elemental buffer damage amounts that attacks/actions can apply
+///
+/// depleting a buffer applies a status effect, see `combat::elements`
 #[derive(Debug, Clone, Copy, PartialEq, Reflect, Default, serde::Deserialize, serde::Serialize)]
 pub enum ElementalEffect {
     /// no elemental effect
@@ -189,34 +281,106 @@ pub enum ElementalEffect {
     Air(f32),
 }
 
+impl Damage {
+    /// physical and elemental damage added together,
+    /// used for things that take damage without elemental buffers
+    pub const fn total(&self) -> f32 {
+        self.physical.0 + self.elemental.amount()
+    }
+}
+
+impl ElementalEffect {
+    /// damage this effect applies too its elemental buffer
+    pub const fn amount(self) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::Earth(v) | Self::Fire(v) | Self::Water(v) | Self::Air(v) => v,
+        }
+    }
+}
+
+/// element an elemental buffer resists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Element {
+    /// slows character when depleted
+    Earth,
+    /// burns character when depleted
+    Fire,
+    /// soaks character when depleted
+    Water,
+    /// gusts character around when depleted
+    Air,
+}
+
 /// resistances too different attack special effects
 #[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Deserialize, serde::Serialize)]
 pub struct ElementalBuffers {
-    /// max earth element before effect
+    /// earth element left before effect
     earth: f32,
-    /// max fire element before effect
+    /// fire element left before effect
     fire: f32,
-    /// max water element before effect
+    /// water element left before effect
     water: f32,
-    /// max air element before effect
+    /// air element left before effect
     air: f32,
-    /// how fast the buffer recharges
+    /// value each buffer recharges too
+    max: f32,
+    /// how fast the buffer recharges, per second
     repair_rate: f32,
 }
 
 /// modifier applied too player stats
 #[derive(Debug, Clone, Reflect, PartialEq)]
 pub struct Effect {
+    /// id of what created this effect,
+    /// reapplying an effect with the same id refreshes and stacks it instead of taking another slot
+    pub id: String,
+    /// attribute this effect modifies
+    pub target: AttributeTarget,
     /// duration of this effect
     duration: EffectDuration,
     /// what this effect does
     apply_type: EffectType,
+    /// time left before `OverTime` effects wear off
+    remaining: Timer,
+    /// how many times this effect is stacked
+    stacks: u32,
+    /// max times this effect can stack, reapplying past this only refreshes it
+    max_stacks: u32,
+}
+
+/// attribute an `Effect` modifies
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, serde::Deserialize, serde::Serialize,
+)]
+pub enum AttributeTarget {
+    /// `Attributes::max_hp`
+    MaxHp,
+    /// `Attributes::max_mana`
+    MaxMana,
+    /// `Attributes::strength`
+    Strength,
+    /// `Attributes::agility`
+    Agility,
+    /// `Attributes::intelligence`
+    Intelligence,
+    /// `Attributes::armor`
+    Armor,
+    /// `Attributes::hp_regen`
+    HpRegen,
+    /// `Attributes::mana_regen`
+    ManaRegen,
+    /// `Attributes::base_speed`
+    BaseSpeed,
+    /// `Attributes::range`
+    Range,
+    /// `Attributes::arm_speed`
+    ArmSpeed,
 }
 
 /// effects value and how it is applied
 #[derive(Debug, Clone, PartialEq, Reflect)]
 pub enum EffectType {
-    // TODO: impl buff modifiers.
     // this is multiplication
     /// SIGNED percentage modifiers. +5% -10% +11.5% etc
     PercentModifier(f32),
@@ -228,12 +392,12 @@ pub enum EffectType {
 
 /// how long should this effect last
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Reflect)]
-enum EffectDuration {
+pub enum EffectDuration {
     /// effect never wears off unless removed or entitity dies
     Infinite,
-    /// effects value is applied over total duration
+    /// effects value is applied until duration runs out
     OverTime(Duration),
-    /// all of this effect is applied instantly
+    /// all of this effect is applied instantly and permanently
     Instant,
 }
 
@@ -278,6 +442,134 @@ impl Default for EffectQueue {
     }
 }
 
+impl EffectQueue {
+    /// iterator over buffs and debuffs applied too character
+    pub fn iter_effects(&self) -> impl Iterator<Item = &Effect> {
+        self.buffs.iter().chain(self.debuffs.iter())
+    }
+
+    /// adds `effect` too queue, an effect with the same id is refreshed and stacked instead.
+    /// full queues drop there oldest effect of the same kind,
+    /// returns false if `effect` could not be added
+    pub fn push_effect(&mut self, effect: Effect) -> bool {
+        if let Some(existing) = self
+            .buffs
+            .iter_mut()
+            .chain(self.debuffs.iter_mut())
+            .find(|f| f.id == effect.id)
+        {
+            existing.remaining.reset();
+            existing.stacks = (existing.stacks + 1).min(existing.max_stacks);
+            return true;
+        }
+
+        let max = self.max;
+        let list = if effect.is_buff() {
+            &mut self.buffs
+        } else {
+            &mut self.debuffs
+        };
+        if self.amount >= max {
+            let Some(oldest) = list.pop_front() else {
+                warn!("effect queue is full, dropping effect {}", effect.id);
+                return false;
+            };
+            warn!("effect queue is full, replacing effect {}", oldest.id);
+            self.amount -= 1;
+        }
+
+        list.push_back(effect);
+        self.amount += 1;
+        true
+    }
+
+    /// removes effect with `id` from queue, returns true if it existed
+    pub fn remove_effect(&mut self, id: &str) -> bool {
+        let old_amount = self.amount;
+        self.buffs.retain(|f| f.id != id);
+        self.debuffs.retain(|f| f.id != id);
+        self.amount = (self.buffs.len() + self.debuffs.len()) as u32;
+        self.amount != old_amount
+    }
+
+    /// ticks `OverTime` effects and removes finished ones,
+    /// returns true if any effect was removed
+    fn tick(&mut self, delta: Duration) -> bool {
+        let old_amount = self.amount;
+        for effect in self.buffs.iter_mut().chain(self.debuffs.iter_mut()) {
+            if matches!(effect.duration, EffectDuration::OverTime(_)) {
+                effect.remaining.tick(delta);
+            }
+        }
+        self.buffs.retain(|f| !f.remaining.finished());
+        self.debuffs.retain(|f| !f.remaining.finished());
+        self.amount = (self.buffs.len() + self.debuffs.len()) as u32;
+        self.amount != old_amount
+    }
+
+    /// sets `current` too the total of every effect in queue,
+    /// percentage effects use `attrs` as there base value
+    fn recalculate(&mut self, attrs: &Attributes) {
+        self.current = self.iter_effects().map(|f| f.modifier(attrs)).sum();
+    }
+}
+
+impl Effect {
+    /// creates effect that changes `target` by `apply_type` for `duration`, effect does not stack
+    pub fn new(
+        id: impl Into<String>,
+        target: AttributeTarget,
+        apply_type: EffectType,
+        duration: EffectDuration,
+    ) -> Self {
+        let remaining = match duration {
+            EffectDuration::OverTime(time) => Timer::new(time, TimerMode::Once),
+            // never ticked so never finishes
+            EffectDuration::Infinite | EffectDuration::Instant => {
+                Timer::new(Duration::MAX, TimerMode::Once)
+            }
+        };
+
+        Self {
+            id: id.into(),
+            target,
+            duration,
+            apply_type,
+            remaining,
+            stacks: 1,
+            max_stacks: 1,
+        }
+    }
+
+    /// sets how many times this effect can stack
+    #[must_use]
+    pub fn with_max_stacks(mut self, max_stacks: u32) -> Self {
+        self.max_stacks = max_stacks.max(1);
+        self
+    }
+
+    /// how many times this effect is stacked
+    pub const fn stacks(&self) -> u32 {
+        self.stacks
+    }
+
+    /// does this effect raise its target attribute
+    pub const fn is_buff(&self) -> bool {
+        match self.apply_type {
+            EffectType::PercentModifier(v) | EffectType::ValueModifier(v) => v >= 0.0,
+        }
+    }
+
+    /// attributes this effect adds, percentage effects are a percentage of `attrs`
+    fn modifier(&self, attrs: &Attributes) -> Attributes {
+        let value = match self.apply_type {
+            EffectType::PercentModifier(percent) => attrs.get(self.target) * percent / 100.0,
+            EffectType::ValueModifier(value) => value,
+        };
+        Attributes::from_target(self.target, value * self.stacks as f32)
+    }
+}
+
 impl DamageQueue {
     /// default empty damage queue
     pub const DEFAULT: Self = Self {
@@ -313,11 +605,75 @@ impl Default for ElementalBuffers {
             fire: 500.0,
             water: 500.0,
             air: 500.0,
+            max: 500.0,
             repair_rate: 10.0,
         }
     }
 }
 
+impl Element {
+    /// every element with a buffer
+    pub const ALL: [Self; 4] = [Self::Earth, Self::Fire, Self::Water, Self::Air];
+}
+
+impl ElementalBuffers {
+    /// buffer value left for `element`
+    pub const fn get(&self, element: Element) -> f32 {
+        match element {
+            Element::Earth => self.earth,
+            Element::Fire => self.fire,
+            Element::Water => self.water,
+            Element::Air => self.air,
+        }
+    }
+
+    /// mutable buffer value for `element`
+    fn get_mut(&mut self, element: Element) -> &mut f32 {
+        match element {
+            Element::Earth => &mut self.earth,
+            Element::Fire => &mut self.fire,
+            Element::Water => &mut self.water,
+            Element::Air => &mut self.air,
+        }
+    }
+
+    /// are all buffers recharged
+    pub fn is_full(&self) -> bool {
+        Element::ALL.iter().all(|f| self.get(*f) >= self.max)
+    }
+
+    /// is any buffer depleted
+    pub fn any_depleted(&self) -> bool {
+        Element::ALL.iter().any(|f| self.get(*f) <= 0.0)
+    }
+
+    /// recharges buffers by `repair_rate` for `delta` seconds, buffers never go above max
+    pub fn recharge(&mut self, delta: f32) {
+        let (max, amount) = (self.max, self.repair_rate * delta);
+        for element in Element::ALL {
+            let buffer = self.get_mut(element);
+            *buffer = (*buffer + amount).min(max);
+        }
+    }
+
+    /// returns elements with depleted buffers and refills them,
+    /// buffers must be depleted again before there effect is reapplied
+    pub fn take_depleted(&mut self) -> Vec<Element> {
+        let max = self.max;
+        Element::ALL
+            .into_iter()
+            .filter(|element| {
+                let buffer = self.get_mut(*element);
+                if *buffer > 0.0 {
+                    return false;
+                }
+                *buffer = max;
+                true
+            })
+            .collect()
+    }
+}
+
 impl CharacterStatBundle {
     /// creates character stat bundle from passed attributes
     pub fn from_attrs(attrs: Attributes) -> Self {
@@ -334,6 +690,7 @@ impl Default for CharacterStatBundle {
             stats: CharacterStats::from_attrs(Attributes::CREEP_DEFAULT, None),
             effects: EffectQueue::default(),
             damage: DamageQueue::DEFAULT,
+            elemental: ElementalStatus::default(),
         }
     }
 }
@@ -404,6 +761,31 @@ impl CharacterStats {
         }
     }
 
+    /// returns ref too elemental buffer values
+    pub const fn elemental_buffers(&self) -> &ElementalBuffers {
+        &self.element_buffer
+    }
+
+    /// returns mutable ref too elemental buffer values
+    pub fn elemental_buffers_mut(&mut self) -> &mut ElementalBuffers {
+        &mut self.element_buffer
+    }
+
+    /// attributes without effects applied
+    fn unmodified_attrs(&self) -> Attributes {
+        self.base + self.equipment + self.added
+    }
+
+    /// recalculates final stats from base, equipment, added and `effects` attributes.
+    /// health and mana are kept below there new max values
+    pub fn recalculate(&mut self, effects: &mut EffectQueue) {
+        let unmodified = self.unmodified_attrs();
+        effects.recalculate(&unmodified);
+        self.current = unmodified + effects.current;
+        self.health = self.health.min(self.current.max_hp);
+        self.mana = self.mana.min(self.current.max_mana);
+    }
+
     /// set equpment amount
     pub fn set_equipment_amount(&mut self, amt: u32) {
         self.equipment_amount = amt;
@@ -592,6 +974,42 @@ impl Attributes {
         armor: 10,
     };
 
+    /// value of `target` attribute
+    pub const fn get(&self, target: AttributeTarget) -> f32 {
+        match target {
+            AttributeTarget::MaxHp => self.max_hp,
+            AttributeTarget::MaxMana => self.max_mana,
+            AttributeTarget::Strength => self.strength as f32,
+            AttributeTarget::Agility => self.agility as f32,
+            AttributeTarget::Intelligence => self.intelligence as f32,
+            AttributeTarget::Armor => self.armor as f32,
+            AttributeTarget::HpRegen => self.hp_regen,
+            AttributeTarget::ManaRegen => self.mana_regen,
+            AttributeTarget::BaseSpeed => self.base_speed,
+            AttributeTarget::Range => self.range,
+            AttributeTarget::ArmSpeed => self.arm_speed,
+        }
+    }
+
+    /// attributes with only `target` set too `value`, integer attributes are rounded
+    pub fn from_target(target: AttributeTarget, value: f32) -> Self {
+        let mut attrs = Self::ZERO;
+        match target {
+            AttributeTarget::MaxHp => attrs.max_hp = value,
+            AttributeTarget::MaxMana => attrs.max_mana = value,
+            AttributeTarget::Strength => attrs.strength = value.round() as i32,
+            AttributeTarget::Agility => attrs.agility = value.round() as i32,
+            AttributeTarget::Intelligence => attrs.intelligence = value.round() as i32,
+            AttributeTarget::Armor => attrs.armor = value.round() as i32,
+            AttributeTarget::HpRegen => attrs.hp_regen = value,
+            AttributeTarget::ManaRegen => attrs.mana_regen = value,
+            AttributeTarget::BaseSpeed => attrs.base_speed = value,
+            AttributeTarget::Range => attrs.range = value,
+            AttributeTarget::ArmSpeed => attrs.arm_speed = value,
+        }
+        attrs
+    }
+
     /// multiplies all attributes by passed value
     fn scale(scale: i32) -> Self {
         let scale_integer = scale;
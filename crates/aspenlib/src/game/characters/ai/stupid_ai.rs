@@ -21,6 +21,7 @@ use crate::{
                 AIAutoShootConfig, AIChaseAction, AICombatAggroConfig, AIShootAction,
                 AIWanderAction, AIWanderConfig, AttackScorer, ChaseScorer,
             },
+            components::CharacterMoveState,
             player::PlayerSelectedHero,
         },
         combat::{AttackDirection, EventRequestAttack},
@@ -158,7 +159,12 @@ fn stupid_ai_aggro_manager(
 /// handles enemy's that can chase
 fn chase_action(
     player_query: Query<&Transform, With<PlayerSelectedHero>>,
-    mut enemy_query: Query<(&Transform, &mut LinearVelocity, &AICombatAggroConfig)>,
+    mut enemy_query: Query<(
+        &Transform,
+        &mut LinearVelocity,
+        &AICombatAggroConfig,
+        &CharacterMoveState,
+    )>,
     mut chasing_enemies: Query<(&Actor, &mut ActionState), With<AIChaseAction>>,
 ) {
     let Ok(player_transform) = player_query.get_single() else {
@@ -167,7 +173,9 @@ fn chase_action(
     };
 
     for (Actor(actor), mut state) in &mut chasing_enemies {
-        if let Ok((enemy_transform, mut velocity, combat_cfg)) = enemy_query.get_mut(*actor) {
+        if let Ok((enemy_transform, mut velocity, combat_cfg, move_state)) =
+            enemy_query.get_mut(*actor)
+        {
             let player_pos = player_transform.translation.truncate();
             let enemy_pos = enemy_transform.translation.truncate();
             let direction = (player_pos - enemy_pos).normalize_or_zero();
@@ -197,11 +205,11 @@ fn chase_action(
                     if !actor_in_personal_space {
                         // move towards the player if player is close enough
                         trace!("actor not in shoot range, moving closer");
-                        *velocity = LinearVelocity(direction * 50.);
+                        *velocity = LinearVelocity(move_state.terrain.apply(direction * 50.));
                     } else if actor_in_personal_space {
                         // move away from player if too close
                         trace!("actor in personal space, moving away");
-                        *velocity = LinearVelocity(-direction * 50.);
+                        *velocity = LinearVelocity(move_state.terrain.apply(-direction * 50.));
                     }
                 }
                 ActionState::Cancelled => {
@@ -282,13 +290,14 @@ fn wander_action(
         &mut LinearVelocity,
         &mut Sprite,
         &mut AIWanderConfig,
+        &CharacterMoveState,
     ), Without<MainCamera>>,
     mut thinker_query: Query<(&Actor, &mut ActionState), With<AIWanderAction>>,
     children: Query<&Children>,
     colliders: Query<&Collider>,
 ) {
     for (Actor(actor), mut state) in &mut thinker_query {
-        if let Ok((enemy_transform, mut velocity, _sprite, mut can_meander_tag)) =
+        if let Ok((enemy_transform, mut velocity, _sprite, mut can_meander_tag, move_state)) =
             enemy_query.get_mut(*actor)
         {
             let camera_pos = camera_pos.single().translation.truncate();
@@ -378,7 +387,7 @@ fn wander_action(
                     if distance <= target_deviation {
                         *state = ActionState::Requested;
                     } else {
-                        *velocity = LinearVelocity(direction * 100.);
+                        *velocity = LinearVelocity(move_state.terrain.apply(direction * 100.));
                     }
                 }
                 ActionState::Success | ActionState::Failure => {
@@ -1,6 +1,6 @@
 use bevy::{
     ecs::{component::Component, reflect::ReflectComponent},
-    prelude::{Entity, Vec2},
+    prelude::{Entity, Timer, Vec2},
     reflect::{std_traits::ReflectDefault, Reflect},
     utils::HashMap,
 };
@@ -57,6 +57,29 @@ pub struct CharacterMoveState {
     pub move_perms: AllowedMovement,
     /// actors teleport status
     pub teleport_status: TeleportStatus,
+    /// movement changes from hazard tiles actor is standing on and elemental status effects
+    pub terrain: TerrainEffect,
+}
+
+/// movement changes from the ground a character is standing on
+#[derive(Debug, Reflect, Clone, Copy, Default, PartialEq)]
+pub struct TerrainEffect {
+    /// fraction of speed lost, 0.0 is full speed
+    pub slow: f32,
+    /// velocity added too the characters own movement
+    pub push: Vec2,
+}
+
+/// hazard tiles a character is touching and where it can be put back after falling
+#[derive(Debug, Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+pub struct HazardContacts {
+    /// hazard tiles character is currently touching
+    pub tiles: Vec<Entity>,
+    /// last position character stood on without touching a hazard
+    pub last_safe_position: Option<Vec2>,
+    /// time until damaging hazards hurt this character again
+    pub damage_cooldown: Timer,
 }
 
 /// character items and value
@@ -221,9 +244,23 @@ impl CharacterMoveState {
         move_status: (CurrentMovement::None, MoveDirection::South),
         move_perms: AllowedMovement::Run,
         teleport_status: TeleportStatus::None,
+        terrain: TerrainEffect::NONE,
     };
 }
 
+impl TerrainEffect {
+    /// terrain that does not change movement
+    pub const NONE: Self = Self {
+        slow: 0.0,
+        push: Vec2::ZERO,
+    };
+
+    /// `velocity` after terrain slowdown and push is applied
+    pub fn apply(&self, velocity: Vec2) -> Vec2 {
+        velocity * (1.0 - self.slow) + self.push
+    }
+}
+
 impl TeleportStatus {
     /// was teleport not requested?
     pub fn teleport_not_requested(&self) -> bool {
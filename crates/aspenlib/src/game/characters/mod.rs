@@ -8,10 +8,16 @@ use crate::{
         attributes_stats::CharacterStats,
         characters::{
             boss::EventSpawnBoss,
-            components::{CharacterInventory, CharacterMoveState, CharacterType, CurrentMovement},
+            components::{
+                CharacterInventory, CharacterMoveState, CharacterType, CurrentMovement,
+                HazardContacts, TeleportStatus, TerrainEffect,
+            },
             creeps::EventSpawnCreep,
         },
-        game_world::components::CharacterSpawner,
+        combat::elements::ElementalStatus,
+        game_world::components::{
+            ActorTeleportEvent, CharacterSpawner, HazardTile, TpTriggerEffect,
+        },
     },
     loading::registry::{ActorRegistry, RegistryIdentifier},
     register_types,
@@ -35,7 +41,15 @@ pub struct CharactersPlugin;
 
 impl Plugin for CharactersPlugin {
     fn build(&self, app: &mut App) {
-        register_types!(app, [CharacterType, CharacterMoveState, CharacterInventory]);
+        register_types!(
+            app,
+            [
+                CharacterType,
+                CharacterMoveState,
+                CharacterInventory,
+                HazardContacts
+            ]
+        );
 
         app.add_event::<EventSpawnCharacter>();
         app.add_plugins((
@@ -48,7 +62,12 @@ impl Plugin for CharactersPlugin {
         app.add_systems(
             Update,
             (
-                (update_character_move_state,).run_if(in_state(AppStage::Running)),
+                (
+                    update_character_move_state,
+                    update_terrain_movement,
+                    fall_into_pits,
+                )
+                    .run_if(in_state(AppStage::Running)),
                 spawn_character_on_event
                     .after(TransformSystem::TransformPropagate)
                     .run_if(on_event::<EventSpawnCharacter>),
@@ -57,6 +76,12 @@ impl Plugin for CharactersPlugin {
     }
 }
 
+/// fraction of speed characters lose while walking through mud
+const MUD_SLOW: f32 = 0.5;
+
+/// speed conveyors push characters at
+const CONVEYOR_SPEED: f32 = 60.0;
+
 // TODO: should this be an enum?
 /// spawn character in world
 #[derive(Debug, Reflect, Clone, Event)]
@@ -200,3 +225,82 @@ fn update_character_move_state(
         }
     }
 }
+
+/// updates terrain slowdown and push of characters from the hazard tiles they are touching
+/// and there elemental status effects
+fn update_terrain_movement(
+    hazards: Query<&HazardTile>,
+    mut characters: Query<(
+        &mut CharacterMoveState,
+        &mut HazardContacts,
+        Option<&ElementalStatus>,
+    )>,
+) {
+    for (mut move_state, mut contacts, status) in &mut characters {
+        // hazard tiles are despawned with there level
+        contacts.tiles.retain(|f| hazards.contains(*f));
+
+        let mut terrain = TerrainEffect::NONE;
+        for hazard in contacts.tiles.iter().filter_map(|f| hazards.get(*f).ok()) {
+            match hazard {
+                HazardTile::Mud => terrain.slow = MUD_SLOW,
+                HazardTile::Conveyor(direction) => terrain.push += *direction * CONVEYOR_SPEED,
+                HazardTile::Spikes | HazardTile::Pit | HazardTile::Burning => {}
+            }
+        }
+        // standing on 2 conveyors should not push twice as fast
+        terrain.push = terrain.push.clamp_length_max(CONVEYOR_SPEED);
+        if let Some(status) = status {
+            terrain.slow = terrain.slow.max(status.slow());
+            terrain.push += status.gust_push();
+        }
+
+        if move_state.terrain != terrain {
+            move_state.terrain = terrain;
+        }
+    }
+}
+
+/// moves characters that fall into a pit back too the last position they stood on safely
+fn fall_into_pits(
+    hazards: Query<&HazardTile>,
+    mut characters: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut HazardContacts,
+        &mut CharacterMoveState,
+    )>,
+    mut tp_events: EventWriter<ActorTeleportEvent>,
+) {
+    for (character, transform, mut contacts, mut move_state) in &mut characters {
+        let touching: Vec<HazardTile> = contacts
+            .tiles
+            .iter()
+            .filter_map(|f| hazards.get(*f).ok())
+            .copied()
+            .collect();
+
+        if touching.is_empty() {
+            contacts.last_safe_position = Some(transform.translation().truncate());
+            continue;
+        }
+        if !touching.contains(&HazardTile::Pit)
+            || move_state.teleport_status != TeleportStatus::None
+        {
+            continue;
+        }
+        let Some(safe_position) = contacts.last_safe_position else {
+            warn!("character fell into a pit without a safe position too return too");
+            continue;
+        };
+
+        info!("character fell into a pit, moving too {safe_position}");
+        contacts.tiles.clear();
+        move_state.teleport_status = TeleportStatus::Requested;
+        tp_events.send(ActorTeleportEvent {
+            tp_type: TpTriggerEffect::Global(safe_position),
+            target: Some(character),
+            sender: None,
+        });
+    }
+}
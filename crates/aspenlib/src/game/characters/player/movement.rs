@@ -43,7 +43,7 @@ pub fn update_player_velocity(
         player_stats.attrs().base_speed * WALK_MODIFIER
     };
 
-    let new_velocity = LinearVelocity(delta.xy() * speed);
+    let new_velocity = LinearVelocity(move_state.terrain.apply(delta.xy() * speed));
 
     *velocity = new_velocity;
 }
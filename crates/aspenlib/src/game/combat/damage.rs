@@ -5,6 +5,7 @@ use crate::{
     game::{
         attributes_stats::{CharacterStats, DamageQueue, ProjectileStats},
         characters::{ai::components::AiType, player::PlayerSelectedHero},
+        combat::elements::ElementalStatus,
         components::ActorColliderType,
         game_world::{
             components::{ActorTeleportEvent, TpTriggerEffect},
@@ -30,14 +31,13 @@ pub fn projectile_hits(
     for event in collision_events.read() {
         let CollisionStarted(a_id, b_id) = *event;
 
-        let Some((Ok(bullet_stats), projectile)) = ({
+        let Some((Ok(bullet_stats), projectile, projectile_collider)) = ({
             let mut projectile_colliders = actor_colliders
                 .iter()
                 .filter(|(_, _, at)| at == &&ActorColliderType::Projectile);
             projectile_colliders
                 .find(|f| f.0 == a_id || f.0 == b_id)
-                .map(|f| f.1.get())
-                .map(|f| (projectiles.get(f), f))
+                .map(|f| (projectiles.get(f.1.get()), f.1.get(), f.0))
         }) else {
             continue;
         };
@@ -51,7 +51,15 @@ pub fn projectile_hits(
                 .map(|f| f.1.get())
         }) else {
             // projectile hit something other than character,
-            // only need too handle the projectile
+            // breakable walls are the only other things with a damage queue
+            let hit_object = if projectile_collider == a_id {
+                b_id
+            } else {
+                a_id
+            };
+            if let Ok(mut damage_queue) = damage_queue_query.get_mut(hit_object) {
+                damage_queue.push_damage(bullet_stats.damage);
+            }
             cmds.entity(projectile).despawn_recursive();
             continue;
         };
@@ -104,22 +112,28 @@ pub fn projectile_hits(
 pub fn apply_damage_system(
     mut game_info: ResMut<CurrentRunInformation>,
     mut damaged_characters: Query<
-        (&mut CharacterStats, Entity, &mut DamageQueue),
+        (
+            &mut CharacterStats,
+            Entity,
+            &mut DamageQueue,
+            Option<&ElementalStatus>,
+        ),
         Changed<DamageQueue>,
     >,
     player_controlled: Query<&PlayerSelectedHero>,
 ) {
-    for (mut character_stats, character, mut damage_queue) in &mut damaged_characters {
+    for (mut character_stats, character, mut damage_queue, status) in &mut damaged_characters {
         for damage in damage_queue.iter_queue() {
             if character_stats.get_current_health() <= 0.0 {
                 return;
             }
+            let damage = status.map_or(*damage, |f| f.modify_damage(*damage));
             if player_controlled.get(character).is_ok() {
                 game_info.player_physical_damage_taken += damage.physical.0;
             } else {
                 game_info.enemy_physical_damage_taken += damage.physical.0;
             }
-            character_stats.apply_damage(*damage);
+            character_stats.apply_damage(damage);
         }
         damage_queue.empty_queue();
     }
@@ -0,0 +1,302 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use rand::Rng;
+
+use crate::{
+    colors,
+    game::attributes_stats::{
+        CharacterStats, Damage, DamageQueue, Element, ElementalEffect, PhysicalDamage,
+    },
+};
+
+/// seconds between burn damage hits
+const BURN_TICK_SECONDS: f32 = 0.5;
+
+/// damage burning characters take each burn tick
+const BURN_DAMAGE: f32 = 3.0;
+
+/// fraction of speed slowed characters lose, 0.0 is full speed
+const SLOW_AMOUNT: f32 = 0.4;
+
+/// physical damage multiplier for soaked characters
+const SOAK_DAMAGE_MULTIPLIER: f32 = 1.5;
+
+/// seconds between air gusts changing direction
+const GUST_TICK_SECONDS: f32 = 0.75;
+
+/// speed air gusts push characters at
+const GUST_SPEED: f32 = 150.0;
+
+/// status effect applied when a characters elemental buffer is depleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum ElementalStatusEffect {
+    /// earth: character moves slower
+    Slow,
+    /// fire: character takes damage over time
+    Burn,
+    /// water: character takes more damage and can not burn
+    Soak,
+    /// air: character gets random bursts of uncontrollable speed
+    Gust,
+}
+
+/// status effects from depleted elemental buffers active on a character
+#[derive(Debug, Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+pub struct ElementalStatus {
+    /// active effects and time until they wear off
+    active: HashMap<ElementalStatusEffect, Timer>,
+    /// time until burning character takes damage again
+    burn_tick: Timer,
+    /// time until air gust changes direction
+    gust_tick: Timer,
+    /// velocity added too character from current air gust
+    gust_push: Vec2,
+}
+
+/// sent when an elemental status effect starts or is refreshed on a character
+#[derive(Debug, Event, Clone, Copy)]
+pub struct ElementalStatusStarted {
+    /// character effect was applied too
+    pub character: Entity,
+    /// effect that was applied
+    pub effect: ElementalStatusEffect,
+}
+
+/// sent when an elemental status effect wears off or is removed from a character
+#[derive(Debug, Event, Clone, Copy)]
+pub struct ElementalStatusEnded {
+    /// character effect was removed from
+    pub character: Entity,
+    /// effect that was removed
+    pub effect: ElementalStatusEffect,
+}
+
+impl ElementalStatusEffect {
+    /// effect applied when `element` buffer is depleted
+    pub const fn from_element(element: Element) -> Self {
+        match element {
+            Element::Earth => Self::Slow,
+            Element::Fire => Self::Burn,
+            Element::Water => Self::Soak,
+            Element::Air => Self::Gust,
+        }
+    }
+
+    /// how many seconds this effect lasts
+    pub const fn duration(self) -> f32 {
+        match self {
+            Self::Slow => 5.0,
+            Self::Burn => 4.0,
+            Self::Soak => 6.0,
+            Self::Gust => 4.0,
+        }
+    }
+
+    /// color characters are tinted while this effect is active
+    pub const fn tint(self) -> Srgba {
+        match self {
+            Self::Slow => colors::BURLYWOOD,
+            Self::Burn => colors::ORANGE_RED,
+            Self::Soak => colors::DEEP_SKY_BLUE,
+            Self::Gust => colors::PALE_TURQUOISE,
+        }
+    }
+}
+
+impl ElementalStatus {
+    /// effects in order of which tint is shown first
+    const TINT_ORDER: [ElementalStatusEffect; 4] = [
+        ElementalStatusEffect::Burn,
+        ElementalStatusEffect::Soak,
+        ElementalStatusEffect::Slow,
+        ElementalStatusEffect::Gust,
+    ];
+
+    /// is `effect` active on this character
+    pub fn has(&self, effect: ElementalStatusEffect) -> bool {
+        self.active.contains_key(&effect)
+    }
+
+    /// fraction of speed lost from slowing effects
+    pub fn slow(&self) -> f32 {
+        if self.has(ElementalStatusEffect::Slow) {
+            SLOW_AMOUNT
+        } else {
+            0.0
+        }
+    }
+
+    /// velocity added too character from air gusts
+    pub const fn gust_push(&self) -> Vec2 {
+        self.gust_push
+    }
+
+    /// color character should be tinted, `None` if no effect is active
+    pub fn tint(&self) -> Option<Srgba> {
+        Self::TINT_ORDER
+            .into_iter()
+            .find(|f| self.has(*f))
+            .map(ElementalStatusEffect::tint)
+    }
+
+    /// `damage` after soaking is applied,
+    /// soaked characters take more physical damage and ignore fire
+    pub fn modify_damage(&self, mut damage: Damage) -> Damage {
+        if !self.has(ElementalStatusEffect::Soak) {
+            return damage;
+        }
+
+        damage.physical.0 *= SOAK_DAMAGE_MULTIPLIER;
+        if matches!(damage.elemental, ElementalEffect::Fire(_)) {
+            damage.elemental = ElementalEffect::None;
+        }
+        damage
+    }
+
+    /// starts `effect` or refreshes its duration if it is already active.
+    /// returns false if `effect` could not be applied
+    fn apply(&mut self, effect: ElementalStatusEffect) -> bool {
+        // water puts out fire
+        if effect == ElementalStatusEffect::Burn && self.has(ElementalStatusEffect::Soak) {
+            return false;
+        }
+
+        match effect {
+            ElementalStatusEffect::Burn if !self.has(effect) => {
+                self.burn_tick = Timer::from_seconds(BURN_TICK_SECONDS, TimerMode::Repeating);
+            }
+            ElementalStatusEffect::Gust if !self.has(effect) => {
+                // first gust is rolled on the next tick
+                self.gust_tick = Timer::from_seconds(0.0, TimerMode::Once);
+            }
+            _ => {}
+        }
+        self.active.insert(
+            effect,
+            Timer::from_seconds(effect.duration(), TimerMode::Once),
+        );
+        true
+    }
+
+    /// removes `effect`, returns true if it was active
+    fn remove(&mut self, effect: ElementalStatusEffect) -> bool {
+        if effect == ElementalStatusEffect::Gust {
+            self.gust_push = Vec2::ZERO;
+        }
+        self.active.remove(&effect).is_some()
+    }
+}
+
+/// refills elemental buffers of characters by there repair rate
+pub fn recharge_elemental_buffers(time: Res<Time>, mut characters: Query<&mut CharacterStats>) {
+    for mut stats in &mut characters {
+        if stats.elemental_buffers().is_full() {
+            continue;
+        }
+        stats.elemental_buffers_mut().recharge(time.delta_secs());
+    }
+}
+
+/// applies status effects for depleted elemental buffers.
+/// buffers recharge every frame so every character is checked instead of only changed stats
+pub fn apply_elemental_status(
+    mut characters: Query<(Entity, &mut CharacterStats, &mut ElementalStatus)>,
+    mut started_events: EventWriter<ElementalStatusStarted>,
+    mut ended_events: EventWriter<ElementalStatusEnded>,
+) {
+    for (character, mut stats, mut status) in &mut characters {
+        if !stats.elemental_buffers().any_depleted() {
+            continue;
+        }
+        let depleted = stats.elemental_buffers_mut().take_depleted();
+        for effect in depleted
+            .into_iter()
+            .map(ElementalStatusEffect::from_element)
+        {
+            if !status.apply(effect) {
+                debug!("{effect:?} was blocked for {character}");
+                continue;
+            }
+            started_events.send(ElementalStatusStarted { character, effect });
+
+            if effect == ElementalStatusEffect::Soak && status.remove(ElementalStatusEffect::Burn) {
+                ended_events.send(ElementalStatusEnded {
+                    character,
+                    effect: ElementalStatusEffect::Burn,
+                });
+            }
+        }
+    }
+}
+
+/// ticks active elemental status effects, deals burn damage, rolls air gusts and removes expired effects
+pub fn tick_elemental_status(
+    time: Res<Time>,
+    mut characters: Query<(Entity, &mut ElementalStatus, &mut DamageQueue)>,
+    mut ended_events: EventWriter<ElementalStatusEnded>,
+) {
+    let mut rng = rand::thread_rng();
+    for (character, mut status, mut damage_queue) in &mut characters {
+        if status.active.is_empty() {
+            continue;
+        }
+
+        let mut expired = Vec::new();
+        for (effect, duration) in &mut status.active {
+            if duration.tick(time.delta()).finished() {
+                expired.push(*effect);
+            }
+        }
+        for effect in expired {
+            status.remove(effect);
+            ended_events.send(ElementalStatusEnded { character, effect });
+        }
+
+        if status.has(ElementalStatusEffect::Burn)
+            && status.burn_tick.tick(time.delta()).just_finished()
+        {
+            damage_queue.push_damage(Damage {
+                physical: PhysicalDamage(BURN_DAMAGE),
+                elemental: ElementalEffect::None,
+            });
+        }
+
+        if status.has(ElementalStatusEffect::Gust) && status.gust_tick.tick(time.delta()).finished()
+        {
+            // gusts are bursts, sometimes there is no wind at all
+            status.gust_push = if rng.gen_bool(0.5) {
+                Vec2::from_angle(rng.gen_range(0.0..TAU)) * GUST_SPEED
+            } else {
+                Vec2::ZERO
+            };
+            status.gust_tick = Timer::from_seconds(GUST_TICK_SECONDS, TimerMode::Once);
+        }
+    }
+}
+
+/// tints characters sprite with the color of there active elemental status effect,
+/// sprite color is only touched when an effect starts or ends so other tints are kept
+pub fn tint_elemental_status(
+    mut started_events: EventReader<ElementalStatusStarted>,
+    mut ended_events: EventReader<ElementalStatusEnded>,
+    mut characters: Query<(&ElementalStatus, &mut Sprite)>,
+) {
+    let changed: HashSet<Entity> = started_events
+        .read()
+        .map(|f| f.character)
+        .chain(ended_events.read().map(|f| f.character))
+        .collect();
+
+    for character in changed {
+        let Ok((status, mut sprite)) = characters.get_mut(character) else {
+            continue;
+        };
+        // last effect ending restores the untinted sprite
+        sprite.color = status.tint().unwrap_or(colors::WHITE).into();
+    }
+}
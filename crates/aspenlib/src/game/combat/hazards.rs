@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+
+use crate::game::{
+    attributes_stats::{Damage, DamageQueue, ElementalEffect},
+    characters::components::HazardContacts,
+    game_world::components::HazardTile,
+};
+
+/// seconds between hits from damaging hazard tiles
+const HAZARD_DAMAGE_INTERVAL: f32 = 0.5;
+
+/// damage spikes deal each hit
+const SPIKE_DAMAGE: f32 = 8.0;
+
+/// damage burning floors deal each hit
+const BURNING_DAMAGE: f32 = 4.0;
+
+/// amount burning floors add too the fire buffer each hit
+const BURNING_FIRE_BUFFER: f32 = 40.0;
+
+/// adds damage from spikes and burning floors too characters standing on them
+pub fn hazard_damage(
+    time: Res<Time>,
+    hazards: Query<&HazardTile>,
+    mut characters: Query<(&mut HazardContacts, &mut DamageQueue)>,
+) {
+    for (mut contacts, mut damage_queue) in &mut characters {
+        contacts.damage_cooldown.tick(time.delta());
+
+        let touching: Vec<HazardTile> = contacts
+            .tiles
+            .iter()
+            .filter_map(|f| hazards.get(*f).ok())
+            .copied()
+            .collect();
+        let spikes = touching.contains(&HazardTile::Spikes);
+        let burning = touching.contains(&HazardTile::Burning);
+
+        if !(spikes || burning) || !contacts.damage_cooldown.finished() {
+            continue;
+        }
+
+        let mut damage = Damage::default();
+        if spikes {
+            damage.physical.0 += SPIKE_DAMAGE;
+        }
+        if burning {
+            damage.physical.0 += BURNING_DAMAGE;
+            damage.elemental = ElementalEffect::Fire(BURNING_FIRE_BUFFER);
+        }
+
+        damage_queue.push_damage(damage);
+        contacts.damage_cooldown = Timer::from_seconds(HAZARD_DAMAGE_INTERVAL, TimerMode::Once);
+    }
+}
@@ -3,17 +3,25 @@ use bevy::{ecs::system::SystemParam, prelude::*};
 
 use crate::{
     game::{
-        combat::unarmed::EventAttackUnarmed,
+        combat::{
+            elements::{ElementalStatus, ElementalStatusEnded, ElementalStatusStarted},
+            unarmed::EventAttackUnarmed,
+        },
         items::weapons::{
             components::{WeaponDescriptor, WeaponHolder},
             EventAttackWeapon,
         },
     },
+    register_types,
     utilities::EntityCreator,
     AppStage,
 };
 
 pub mod damage;
+/// status effects from depleted elemental buffers
+pub mod elements;
+/// damage from hazard tiles
+pub mod hazards;
 /// handles attacks from characters without weapons
 pub mod unarmed;
 
@@ -22,9 +30,12 @@ pub struct CombatPlugin;
 
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        register_types!(app, [ElementalStatus]);
         app.add_plugins(unarmed::UnArmedPlugin);
 
-        app.add_event::<EventRequestAttack>();
+        app.add_event::<EventRequestAttack>()
+            .add_event::<ElementalStatusStarted>()
+            .add_event::<ElementalStatusEnded>();
 
         app.add_systems(
             PreUpdate,
@@ -36,6 +47,14 @@ impl Plugin for CombatPlugin {
             (
                 damage::handle_death_system,
                 damage::projectile_hits.run_if(on_event::<CollisionStarted>),
+                hazards::hazard_damage,
+                (
+                    elements::recharge_elemental_buffers,
+                    elements::apply_elemental_status,
+                    elements::tick_elemental_status,
+                    elements::tint_elemental_status,
+                )
+                    .chain(),
                 delegate_attack_events.run_if(on_event::<EventRequestAttack>),
             )
                 .run_if(in_state(AppStage::Running)),
@@ -1,184 +1,279 @@
-use avian2d::prelude::{Collider, CollisionLayers, RigidBody};
-use bevy::prelude::*;
+use avian2d::prelude::{
+    Collider, CollisionEnded, CollisionLayers, CollisionStarted, RigidBody, Sensor,
+};
+use bevy::{prelude::*, utils::HashSet};
 use bevy_ecs_ldtk::TileEnumTags;
 
-use crate::consts::AspenCollisionLayer;
+use crate::{
+    consts::{AspenCollisionLayer, TILE_SIZE},
+    game::{
+        characters::components::HazardContacts, components::ActorColliderType,
+        game_world::components::HazardTile,
+    },
+};
 
-/// tiles that can collide get this
-#[derive(Clone, Debug, Bundle, Default)]
-pub struct LdtkTileCollider {
-    /// name of collider
+/// wall collision rectangles of a tile relative too the tile,
+/// merged into a single collider for the tiles layer by `merge_tile_colliders`
+#[derive(Clone, Debug, Component)]
+pub struct TileWallShape(pub Vec<Rect>);
+
+/// static collider built from every `TileWallShape` in a tile layer
+#[derive(Clone, Debug, Component)]
+pub struct MergedTileColliders;
+
+/// hazard tiles get this, characters touching them are tracked in `HazardContacts`
+#[derive(Clone, Debug, Bundle)]
+pub struct LdtkTileHazard {
+    /// name of hazard
     pub name: Name,
+    /// what this hazard does too characters
+    pub hazard: HazardTile,
     /// entity has physics
     pub rigidbody: RigidBody,
+    /// hazards are walked over, not into
+    pub sensor: Sensor,
     /// collision shape
     pub collision_shape: Collider,
     /// what too collide with
     pub collision_group: CollisionLayers,
 }
 
-// TODO:
-// maybe make this a system the registers a bundle?
-/// checks tile enum tag for collider tag, creates shape for collider, passes too `insert_collider`, tag is then removed from `tile_enum_tags`
-#[allow(clippy::too_many_lines)]
+/// checks tile enum tag for collider tag, adds its shape as a `TileWallShape`, tag is then removed from `tile_enum_tags`
 pub fn handle_and_removed_collider_tag(
     tag: &str,
     cmds: &mut Commands,
     entity: Entity,
     tag_info: &mut Mut<TileEnumTags>,
 ) -> bool {
-    // 90 degrees radian
-    let degrees = std::f32::consts::FRAC_PI_2;
-
-    let tag_was_handled = match tag {
-        "CollideUp" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(0.0, -12.), 0.0, Collider::rectangle(32.0, 8.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideDown" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(0.0, 12.0), 0.0, Collider::rectangle(32.0, 8.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideLeft" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(12.0, 0.0), 0.0, Collider::rectangle(8.0, 32.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideRight" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(-12.0, 0.0), 0.0, Collider::rectangle(8.0, 32.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideCornerLR" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(-11.0, 11.0), 0.0, Collider::rectangle(10.0, 10.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideCornerUR" => {
-            let shape: Vec<(Vec2, f32, Collider)> = vec![(
-                Vec2::new(-11.0, -11.0),
-                0.0,
-                Collider::rectangle(10.0, 10.0),
-            )];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideCornerLL" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(11.0, 11.0), 0.0, Collider::rectangle(10.0, 10.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideCornerUL" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(11.0, -11.0), 0.0, Collider::rectangle(10.0, 10.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideInnerUL" => {
-            let shape: Vec<(Vec2, f32, Collider)> = vec![
-                (
-                    Vec2::new(-12.0, -4.0),
-                    degrees,
-                    Collider::rectangle(24.0, 8.0),
-                ),
-                (Vec2::new(0.0, 12.0), 0.0, Collider::rectangle(32.0, 8.0)),
-            ];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideInnerLL" => {
-            let shape: Vec<(Vec2, f32, Collider)> = vec![
-                (
-                    Vec2::new(-12.0, 4.0),
-                    degrees,
-                    Collider::rectangle(24.0, 8.0),
-                ),
-                (Vec2::new(0.0, -12.0), 0.0, Collider::rectangle(32.0, 8.0)),
-            ];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideInnerUR" => {
-            let shape: Vec<(Vec2, f32, Collider)> = vec![
-                (
-                    Vec2::new(12.0, -4.0),
-                    degrees,
-                    Collider::rectangle(24.0, 8.0),
-                ),
-                (Vec2::new(0.0, 12.0), 0.0, Collider::rectangle(32.0, 8.0)),
-            ];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
+    let Some(shape) = collider_tag_shape(tag) else {
+        return false;
+    };
+
+    cmds.entity(entity)
+        .insert((Name::new(tag.to_owned()), TileWallShape(shape)));
+    tag_info.tags.retain(|f| f != tag);
+    true
+}
+
+/// wall rectangles for a collider tag, relative too the tile center.
+/// `None` if tag is not a collider tag
+fn collider_tag_shape(tag: &str) -> Option<Vec<Rect>> {
+    // center and size of each part of the wall
+    let parts: &[(Vec2, Vec2)] = match tag {
+        "CollideUp" => &[(Vec2::new(0.0, -12.0), Vec2::new(32.0, 8.0))],
+        "CollideDown" => &[(Vec2::new(0.0, 12.0), Vec2::new(32.0, 8.0))],
+        "CollideLeft" => &[(Vec2::new(12.0, 0.0), Vec2::new(8.0, 32.0))],
+        "CollideRight" => &[(Vec2::new(-12.0, 0.0), Vec2::new(8.0, 32.0))],
+        "CollideCornerLR" => &[(Vec2::new(-11.0, 11.0), Vec2::new(10.0, 10.0))],
+        "CollideCornerUR" => &[(Vec2::new(-11.0, -11.0), Vec2::new(10.0, 10.0))],
+        "CollideCornerLL" => &[(Vec2::new(11.0, 11.0), Vec2::new(10.0, 10.0))],
+        "CollideCornerUL" => &[(Vec2::new(11.0, -11.0), Vec2::new(10.0, 10.0))],
+        "CollideInnerUL" => &[
+            (Vec2::new(-12.0, -4.0), Vec2::new(8.0, 24.0)),
+            (Vec2::new(0.0, 12.0), Vec2::new(32.0, 8.0)),
+        ],
+        "CollideInnerLL" => &[
+            (Vec2::new(-12.0, 4.0), Vec2::new(8.0, 24.0)),
+            (Vec2::new(0.0, -12.0), Vec2::new(32.0, 8.0)),
+        ],
+        "CollideInnerUR" => &[
+            (Vec2::new(12.0, -4.0), Vec2::new(8.0, 24.0)),
+            (Vec2::new(0.0, 12.0), Vec2::new(32.0, 8.0)),
+        ],
+        "CollideInnerLR" => &[
+            (Vec2::new(12.0, 4.0), Vec2::new(8.0, 24.0)),
+            (Vec2::new(0.0, -12.0), Vec2::new(32.0, 8.0)),
+        ],
+        "DoubleWallVertical" => &[
+            (Vec2::new(12.0, 4.0), Vec2::new(8.0, 32.0)),
+            (Vec2::new(-12.0, 4.0), Vec2::new(8.0, 32.0)),
+        ],
+        "DoubleWallHorizontal" => &[
+            (Vec2::new(12.0, 4.0), Vec2::new(32.0, 8.0)),
+            (Vec2::new(-12.0, 4.0), Vec2::new(32.0, 8.0)),
+        ],
+        "CollideInnerWall" | "CollideOuterWall" => &[(Vec2::new(0.0, 14.0), Vec2::new(32.0, 8.0))],
+        _ => return None,
+    };
+
+    Some(
+        parts
+            .iter()
+            .map(|(center, size)| Rect::from_center_size(*center, *size))
+            .collect(),
+    )
+}
+
+/// rebuilds the merged wall collider of every tile layer that got new `TileWallShape`s.
+/// touching wall rectangles are merged so each layer needs only a few rectangles
+pub fn merge_tile_colliders(
+    mut cmds: Commands,
+    new_shapes: Query<&Parent, Added<TileWallShape>>,
+    layers: Query<&Children>,
+    tile_shapes: Query<(&Transform, &TileWallShape)>,
+    merged_colliders: Query<(), With<MergedTileColliders>>,
+) {
+    let changed_layers: HashSet<Entity> = new_shapes.iter().map(Parent::get).collect();
+
+    for layer in changed_layers {
+        let Ok(children) = layers.get(layer) else {
+            continue;
+        };
+
+        let mut rects = Vec::new();
+        for child in children {
+            if merged_colliders.contains(*child) {
+                cmds.entity(*child).despawn_recursive();
+                continue;
+            }
+            let Ok((tile_transform, shape)) = tile_shapes.get(*child) else {
+                continue;
+            };
+            rects.extend(shape.0.iter().map(|rect| {
+                let min = tile_transform.transform_point(rect.min.extend(0.0));
+                let max = tile_transform.transform_point(rect.max.extend(0.0));
+                // tile shapes are whole pixels
+                IRect::from_corners(
+                    min.truncate().round().as_ivec2(),
+                    max.truncate().round().as_ivec2(),
+                )
+            }));
         }
-        "CollideInnerLR" => {
-            let shape: Vec<(Vec2, f32, Collider)> = vec![
-                (
-                    Vec2::new(12.0, 4.0),
-                    degrees,
-                    Collider::rectangle(24.0, 8.0),
-                ),
-                (Vec2::new(0.0, -12.0), 0.0, Collider::rectangle(32.0, 8.0)),
-            ];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
+
+        let merged = merge_rects(rects);
+        if merged.is_empty() {
+            continue;
         }
-        "DoubleWallVertical" => {
-            let shape: Vec<(Vec2, f32, Collider)> = vec![
-                (
-                    Vec2::new(12.0, 4.0),
-                    degrees,
-                    Collider::rectangle(32.0, 8.0),
-                ),
+        trace!("merged layer walls into {} rectangles", merged.len());
+
+        let parts = merged
+            .iter()
+            .map(|rect| {
+                let rect = rect.as_rect();
                 (
-                    Vec2::new(-12.0, 4.0),
-                    degrees,
-                    Collider::rectangle(32.0, 8.0),
-                ),
-            ];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "DoubleWallHorizontal" => {
-            let shape: Vec<(Vec2, f32, Collider)> = vec![
-                (Vec2::new(12.0, 4.0), 0.0, Collider::rectangle(32.0, 8.0)),
-                (Vec2::new(-12.0, 4.0), 0.0, Collider::rectangle(32.0, 8.0)),
-            ];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
-        }
-        "CollideInnerWall" | "CollideOuterWall" => {
-            let shape: Vec<(Vec2, f32, Collider)> =
-                vec![(Vec2::new(0.0, 14.0), 0.0, Collider::rectangle(32.0, 8.0))];
-            insert_tile_collider(cmds, entity, shape, tag);
-            true
+                    rect.center(),
+                    0.0,
+                    Collider::rectangle(rect.width(), rect.height()),
+                )
+            })
+            .collect();
+        cmds.entity(layer).with_children(|layer_parts| {
+            layer_parts.spawn((
+                Name::new("MergedTileColliders"),
+                MergedTileColliders,
+                Transform::default(),
+                RigidBody::Static,
+                Collider::compound(parts),
+                AspenCollisionLayer::static_object(),
+            ));
+        });
+    }
+}
+
+/// merges touching rectangles into rows, then rows into columns.
+/// area covered by the rectangles does not change
+fn merge_rects(rects: Vec<IRect>) -> Vec<IRect> {
+    merge_rects_along(merge_rects_along(rects, 0), 1)
+}
+
+/// merges rectangles that touch along `axis` and cover the same span on the other axis
+fn merge_rects_along(mut rects: Vec<IRect>, axis: usize) -> Vec<IRect> {
+    let cross = 1 - axis;
+    rects.sort_unstable_by_key(|f| (f.min[cross], f.max[cross], f.min[axis]));
+
+    let mut merged: Vec<IRect> = Vec::with_capacity(rects.len());
+    for rect in rects {
+        if let Some(last) = merged.last_mut() {
+            let same_span =
+                last.min[cross] == rect.min[cross] && last.max[cross] == rect.max[cross];
+            if same_span && rect.min[axis] <= last.max[axis] {
+                *last = last.union(rect);
+                continue;
+            }
         }
-        _ => false,
-    };
-    if tag_was_handled {
-        tag_info.tags.retain(|f| f != tag);
+        merged.push(rect);
     }
-    tag_was_handled
+    merged
 }
 
-/// inserts collider onto passed entity, collides with everything
-fn insert_tile_collider(
-    commands: &mut Commands,
-    entity: Entity,
-    shape: Vec<(Vec2, f32, Collider)>,
+/// checks tile enum tag for hazard tag, inserts sensor collider with the tags `HazardTile`, tag is then removed from `tile_enum_tags`
+pub fn handle_and_removed_hazard_tag(
     tag: &str,
-) {
-    commands.entity(entity).insert((LdtkTileCollider {
+    cmds: &mut Commands,
+    entity: Entity,
+    tag_info: &mut Mut<TileEnumTags>,
+) -> bool {
+    let hazard = match tag {
+        "HazardSpikes" => HazardTile::Spikes,
+        "HazardPit" => HazardTile::Pit,
+        "HazardMud" => HazardTile::Mud,
+        "HazardBurning" => HazardTile::Burning,
+        "ConveyorUp" => HazardTile::Conveyor(Vec2::Y),
+        "ConveyorDown" => HazardTile::Conveyor(Vec2::NEG_Y),
+        "ConveyorLeft" => HazardTile::Conveyor(Vec2::NEG_X),
+        "ConveyorRight" => HazardTile::Conveyor(Vec2::X),
+        _ => return false,
+    };
+
+    // characters only fall once they are over the middle of a pit
+    let size = if hazard == HazardTile::Pit {
+        TILE_SIZE * 0.5
+    } else {
+        TILE_SIZE
+    };
+
+    cmds.entity(entity).insert(LdtkTileHazard {
         name: Name::new(tag.to_owned()),
+        hazard,
         rigidbody: RigidBody::Static,
-        collision_shape: Collider::compound(shape),
-        collision_group: AspenCollisionLayer::static_object(),
-    },));
+        sensor: Sensor,
+        collision_shape: Collider::rectangle(size, size),
+        collision_group: AspenCollisionLayer::hazard_tile(),
+    });
+    tag_info.tags.retain(|f| f != tag);
+    true
+}
+
+/// tracks hazard tiles each character is touching in there `HazardContacts`
+pub fn track_hazard_contacts(
+    mut collision_start_events: EventReader<CollisionStarted>,
+    mut collision_end_events: EventReader<CollisionEnded>,
+    actor_colliders: Query<(&Parent, &ActorColliderType), With<Collider>>,
+    hazards: Query<(), With<HazardTile>>,
+    mut characters: Query<&mut HazardContacts>,
+) {
+    let started = collision_start_events
+        .read()
+        .map(|CollisionStarted(a, b)| (*a, *b, true));
+    let ended = collision_end_events
+        .read()
+        .map(|CollisionEnded(a, b)| (*a, *b, false));
+
+    for (collider_a, collider_b, touching) in started.chain(ended) {
+        let (hazard, collider) = if hazards.contains(collider_a) {
+            (collider_a, collider_b)
+        } else if hazards.contains(collider_b) {
+            (collider_b, collider_a)
+        } else {
+            continue;
+        };
+
+        let Some(character) = actor_colliders
+            .get(collider)
+            .ok()
+            .filter(|(_, at)| **at == ActorColliderType::Character)
+            .map(|(parent, _)| parent.get())
+        else {
+            continue;
+        };
+        let Ok(mut contacts) = characters.get_mut(character) else {
+            continue;
+        };
+
+        if !touching {
+            contacts.tiles.retain(|f| *f != hazard);
+        } else if !contacts.tiles.contains(&hazard) {
+            contacts.tiles.push(hazard);
+        }
+    }
 }
@@ -50,10 +50,43 @@ pub struct CharacterSpawner {
     pub spawned_characters: Vec<Entity>,
 }
 
+impl CharacterSpawner {
+    /// true if spawner can still create characters,
+    /// random wave spawners stop once they have spawned `max_spawned` characters
+    pub fn has_waves_left(&self) -> bool {
+        if self.random_wave {
+            self.spawned_characters.len() < usize::try_from(self.max_spawned).unwrap_or_default()
+        } else {
+            !self.waves.is_empty()
+        }
+    }
+}
+
+/// spawner is waiting for its room encounter too start
+#[derive(Debug, Component, Default, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct DormantSpawner;
+
 /// tile is a room exit
 #[derive(Component, Default, Debug, Clone)]
 pub struct RoomExitTile;
 
+/// environmental hazard created from a ldtk `CollisionType` tile tag
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum HazardTile {
+    /// hurts characters standing on it
+    Spikes,
+    /// characters fall in and are moved back too there last safe position
+    Pit,
+    /// slows characters walking through it
+    Mud,
+    /// burns characters standing on it
+    Burning,
+    /// pushes characters standing on it in this direction
+    Conveyor(Vec2),
+}
+
 // TODO: get rid of this, it feels like a dirty ass hack
 /// room border markers
 #[derive(Component)]
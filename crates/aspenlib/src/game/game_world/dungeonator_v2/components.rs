@@ -1,3 +1,5 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
 use bevy::{
     ecs::{
         bundle::Bundle,
@@ -5,8 +7,8 @@ use bevy::{
         system::Resource,
     },
     log::warn,
-    math::{IVec2, Vec2},
-    prelude::{Component, IRect, Name, Transform, Visibility},
+    math::{IVec2, Quat, Rect, UVec2, Vec2, Vec3},
+    prelude::{Component, Deref, DerefMut, GlobalTransform, IRect, Name, Transform, Visibility},
     reflect::Reflect,
 };
 use bevy_ecs_ldtk::{
@@ -14,15 +16,19 @@ use bevy_ecs_ldtk::{
     LdtkProjectHandle, LevelIid,
 };
 use bevy_ecs_tilemap::prelude::TilemapSize;
+use rand::{rngs::StdRng, SeedableRng};
 
 use crate::{
     consts::TILE_SIZE,
     game::game_world::{
         components::RoomExit,
         dungeonator_v2::{
-            hallways::HallWayBlueprint, room_graph::RoomGraph, tile_graph::TileGraph,
+            hallways::{HallWayBlueprint, MAX_HALLWAY_WIDTH, MIN_HALLWAY_WIDTH},
+            room_graph::{rules::PlacementRule, RoomGraph},
+            tile_graph::{TileGraph, TileType},
         },
     },
+    loading::registry::RegistryIdentifier,
 };
 
 /// bundle for easy spawning of dungeon
@@ -39,6 +45,8 @@ pub struct DungeonContainerBundle {
     pub spatial: Transform,
     /// visibility
     pub visual: Visibility,
+    /// seeded rng used for every generation step
+    pub rng: DungeonRng,
 }
 
 /// placeable room preset
@@ -72,7 +80,7 @@ pub struct DungeonHallWayBundle {
 /// database generated from ldtk level assets on startup or when assets are changed,
 /// splits levels based on level attributes
 #[allow(unused)]
-#[derive(Debug, Resource, Reflect, Default)]
+#[derive(Debug, Clone, Resource, Reflect, Default)]
 #[reflect(Resource)]
 pub struct DungeonRoomDatabase {
     /// list of hideout room presets
@@ -83,6 +91,8 @@ pub struct DungeonRoomDatabase {
     pub end_rooms: Vec<RoomPreset>,
     /// list of special room presets
     pub special_rooms: Vec<RoomPreset>,
+    /// list of secret room presets, only reachable by breaking a wall
+    pub secret_rooms: Vec<RoomPreset>,
     /// list of 32 tile x 32 tile room presets
     pub small_short_rooms: Vec<RoomPreset>,
     /// list of 32 tile x 64 tile room presets
@@ -101,25 +111,96 @@ pub struct DungeonRoomDatabase {
     pub huge_long_rooms: Vec<RoomPreset>,
 }
 
-// TODO: add dungeon level too settings
 /// settings to configure the dungeon generator,
 /// `useable_rooms` and hallways are filled by other systems
 #[derive(Debug, Clone, Default, Reflect)]
 pub struct DungeonSettings {
-    /// level for leveled rooms
-    pub level: RoomLevel,
+    /// seed this dungeon was generated from
+    pub seed: DungeonSeed,
+    /// floor of the current run this dungeon is, first floor is 0
+    pub floor: usize,
+    /// room levels that can be chosen for this dungeon
+    pub room_levels: Vec<RoomLevel>,
+    /// theme rooms are chosen from
+    pub biome: RoomBiome,
+    /// identifier of the ldtk tileset hallways are built from
+    pub hallway_tileset: String,
+    /// bosses that can replace the end room boss
+    pub boss_pool: Vec<RegistryIdentifier>,
     /// border around outside of dungeon in tiles
     pub border: u32,
     /// how wide/tall this dungeon should be in tiles
     pub size: TilemapSize,
-    // TODO: readd, disabled because it breaks maths
-    // /// minimum space between dungeon rooms, in tiles
-    // pub tiles_between_rooms: i32,
+    /// minimum space between dungeon rooms, in tiles
+    pub tiles_between_rooms: u32,
     /// amount of rooms inside dungeon
     pub distribution: RoomDistribution,
     /// percentage of paths between
     /// rooms that are chosen to loop
     pub hallway_loop_chance: f32,
+    /// costs used when routing hallways
+    pub path_costs: HallwayPathCosts,
+    /// how wide hallways are built
+    pub hallway_widths: HallwayWidths,
+    /// rules the room graph must follow, layouts breaking them are repaired or retried
+    pub placement_rules: Vec<PlacementRule>,
+}
+
+/// costs used by the hallway router, higher penalties make hallways avoid that thing harder
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize, serde::Serialize)]
+pub struct HallwayPathCosts {
+    /// cost of moving a single tile
+    pub step: f32,
+    /// added cost for changing direction
+    pub turn: f32,
+    /// added cost for moving next too a room
+    pub wall_adjacent: f32,
+    /// added cost for moving onto an existing hallway
+    pub hallway_crossing: f32,
+}
+
+impl Default for HallwayPathCosts {
+    fn default() -> Self {
+        Self {
+            step: 1.0,
+            turn: 4.0,
+            wall_adjacent: 3.0,
+            hallway_crossing: 8.0,
+        }
+    }
+}
+
+/// width of dungeon hallways in tiles,
+/// widths are kept between `MIN_HALLWAY_WIDTH` and `MAX_HALLWAY_WIDTH`
+#[derive(Debug, Clone, Reflect, serde::Deserialize, serde::Serialize)]
+pub struct HallwayWidths {
+    /// width of hallways not connected too a room in `room_types`
+    pub default: u32,
+    /// width of hallways connected too a room of this type, widest match is used
+    #[serde(default)]
+    pub room_types: Vec<(RoomType, u32)>,
+}
+
+impl Default for HallwayWidths {
+    fn default() -> Self {
+        Self {
+            default: MIN_HALLWAY_WIDTH,
+            room_types: Vec::new(),
+        }
+    }
+}
+
+impl HallwayWidths {
+    /// width of a hallway connecting rooms of type `a` and `b`
+    pub fn width_between(&self, a: &RoomType, b: &RoomType) -> u32 {
+        self.room_types
+            .iter()
+            .filter(|(rtype, _)| rtype == a || rtype == b)
+            .map(|(_, width)| *width)
+            .max()
+            .unwrap_or(self.default)
+            .clamp(MIN_HALLWAY_WIDTH, MAX_HALLWAY_WIDTH)
+    }
 }
 
 impl DungeonSettings {
@@ -135,6 +216,28 @@ impl DungeonSettings {
     }
 }
 
+/// seed for dungeon generation,
+/// same seed and same room database always creates the same dungeon
+#[derive(Debug, Clone, Copy, Default, Reflect, PartialEq, Eq, Hash)]
+pub struct DungeonSeed(pub u64);
+
+impl std::fmt::Display for DungeonSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// seeded rng for a dungeon, every generation step pulls from this
+#[derive(Debug, Component, Deref, DerefMut)]
+pub struct DungeonRng(pub StdRng);
+
+impl DungeonRng {
+    /// creates rng from `seed`
+    pub fn from_seed(seed: DungeonSeed) -> Self {
+        Self(StdRng::seed_from_u64(seed.0))
+    }
+}
+
 /// self contained dungeon data component
 #[derive(Debug, Component, Default, Reflect)]
 #[reflect(Component)]
@@ -146,6 +249,8 @@ pub struct Dungeon {
     /// graph of rooms and hallway connections
     #[reflect(ignore)]
     pub room_graph: RoomGraph,
+    /// where the player is placed when entering this dungeon, relative too dungeon
+    pub start_position: Vec2,
 }
 
 /// current boss combat state
@@ -176,31 +281,102 @@ pub struct RoomPreset {
     pub size: IVec2,
     /// exit offsets for current room
     pub exits: Vec<IVec2>,
+    /// tile collision data for this room
+    pub collision: RoomCollisionGrid,
+    /// orientations this room can be placed with, always has `RoomOrientation::Identity`
+    /// and only orientations that `keeps_art_upright`
+    pub orientations: Vec<RoomOrientation>,
+}
+
+/// walkability of each tile in a room preset,
+/// extracted from the level asset so layouts can be made without spawning levels
+#[derive(Debug, Clone, Default, Reflect, PartialEq, Eq)]
+pub struct RoomCollisionGrid {
+    /// width of room in tiles
+    pub width: u32,
+    /// height of room in tiles
+    pub height: u32,
+    /// tile types, row major with row 0 being the bottom of the room
+    pub tiles: Vec<TileType>,
+}
+
+impl RoomCollisionGrid {
+    /// creates grid of `width` x `height` filled with `TileType::Unused`
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![TileType::Unused; (width * height) as usize],
+        }
+    }
+
+    /// returns tile type at `x` `y`, `TileType::Unused` if outside the grid
+    pub fn get(&self, x: u32, y: u32) -> TileType {
+        if x >= self.width || y >= self.height {
+            return TileType::Unused;
+        }
+        self.tiles[(y * self.width + x) as usize]
+    }
+
+    /// sets tile type at `x` `y`, does nothing if outside the grid
+    pub fn set(&mut self, x: u32, y: u32, tile: TileType) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        self.tiles[(y * self.width + x) as usize] = tile;
+    }
+
+    /// creates copy of this grid turned by `orientation`
+    pub fn oriented(&self, orientation: RoomOrientation) -> Self {
+        let size = IVec2::new(self.width as i32, self.height as i32);
+        let oriented_size = orientation.oriented_size(size).as_uvec2();
+        let mut grid = Self::new(oriented_size.x, oriented_size.y);
+
+        for x in 0..self.width {
+            for y in 0..self.height {
+                let tile = orientation
+                    .orient_tile(UVec2 { x, y }.as_ivec2(), size)
+                    .as_uvec2();
+                grid.set(tile.x, tile.y, self.get(x, y));
+            }
+        }
+
+        grid
+    }
 }
 
 impl RoomBlueprint {
-    /// creates `RoomBlueprint` from a room preset
-    pub fn from_preset(preset: &RoomPreset, position: IVec2, id: u32) -> Self {
+    /// creates `RoomBlueprint` from a room preset placed with `orientation`
+    pub fn from_preset(
+        preset: &RoomPreset,
+        position: IVec2,
+        orientation: RoomOrientation,
+        id: u32,
+    ) -> Self {
+        let tile_size = TILE_SIZE as i32;
+        let preset_tiles = preset.size / tile_size;
         let room_exits = preset
             .exits
             .iter()
             .map(|f| RoomExit {
                 parent: RoomID(id),
                 hallway_connected: false,
-                position: IVec2 {
-                    x: (position.x + f.x),
-                    y: (position.y + f.y),
-                },
+                position: position
+                    + orientation.orient_tile(*f / tile_size, preset_tiles) * tile_size,
             })
             .collect::<Vec<RoomExit>>();
 
         Self {
             descriptor: preset.descriptor.clone(),
             asset_id: preset.room_asset_id.clone(),
-            room_space: IRect::from_corners(position, position + preset.size),
+            room_space: IRect::from_corners(
+                position,
+                position + orientation.oriented_size(preset.size),
+            ),
             name: preset.name.clone(),
             id: RoomID(id),
             exits: room_exits,
+            orientation,
         }
     }
 
@@ -208,6 +384,21 @@ impl RoomBlueprint {
     pub fn center(&self) -> IVec2 {
         self.room_space.center()
     }
+
+    /// transform for the rooms level entity, turns the level so it fills `room_space`
+    pub fn transform(&self) -> Transform {
+        self.orientation.room_transform(
+            self.room_space.min.as_vec2(),
+            self.room_space.size().as_vec2(),
+        )
+    }
+
+    /// area covered by room in worldspace, `room_transform` is the rooms `GlobalTransform`
+    pub fn world_rect(&self, room_transform: &GlobalTransform) -> Rect {
+        let size = self.room_space.size().as_vec2();
+        let min = room_transform.translation().truncate() - self.orientation.pivot_offset(size);
+        Rect::from_corners(min, min + size)
+    }
 }
 
 /// unique id of room per dungeon spawn
@@ -230,6 +421,8 @@ pub struct RoomBlueprint {
     pub room_space: IRect,
     /// rooms unique number
     pub id: RoomID,
+    /// how the rooms preset was turned when placed
+    pub orientation: RoomOrientation,
 }
 
 /// room stats, describes room for placing algorithm
@@ -241,10 +434,12 @@ pub struct RoomDescriptor {
     pub level: RoomLevel,
     /// what function does this room serve for the dungeon
     pub rtype: RoomType,
+    /// theme this room belongs too
+    pub biome: RoomBiome,
 }
 
 /// amounts of each room that should be spawned
-#[derive(Debug, Clone, Default, Reflect)]
+#[derive(Debug, Clone, Default, Reflect, serde::Deserialize, serde::Serialize)]
 pub struct RoomDistribution {
     /// max amount of this room too spawn
     pub small_short: i32,
@@ -264,10 +459,24 @@ pub struct RoomDistribution {
     pub huge_long: i32,
     /// max amount of this room too spawn
     pub special: i32,
+    /// max amount of this room too spawn
+    #[serde(default)]
+    pub secret: i32,
 }
 
 /// what level is this room
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Default, Ord, Reflect)]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Default,
+    Ord,
+    Reflect,
+    serde::Deserialize,
+    serde::Serialize,
+)]
 pub enum RoomLevel {
     /// DEBUG LEVEL
     Level0,
@@ -282,25 +491,60 @@ pub enum RoomLevel {
     Level4,
 }
 
-impl RoomLevel {
-    /// returns the advanced '`RoomLevel`'
-    pub const fn next_level(self) -> Self {
-        match self {
-            Self::Level0 => Self::Level0,
-            Self::Level1 => Self::Level2,
-            Self::Level2 => Self::Level3,
-            Self::Level3 | Self::Level4 => Self::Level4,
+/// theme of a room, floors only use rooms from there biome
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Default,
+    Ord,
+    Hash,
+    Reflect,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum RoomBiome {
+    /// stone halls, used by rooms without a biome
+    #[default]
+    Crypt,
+    /// wet tunnels
+    Sewer,
+    /// overgrown ruins
+    Forest,
+}
+
+impl RoomBiome {
+    /// gets biome from its `IdentBiome` ldtk enum value
+    pub fn from_ldtk(value: &str) -> Option<Self> {
+        match value {
+            "Crypt" => Some(Self::Crypt),
+            "Sewer" => Some(Self::Sewer),
+            "Forest" => Some(Self::Forest),
+            _ => None,
         }
     }
 }
 
 /// what function does this room serve in the dungeon
-#[derive(Debug, Clone, Reflect, PartialEq, Eq, PartialOrd, Default, Ord)]
+#[derive(
+    Debug,
+    Clone,
+    Reflect,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Default,
+    Ord,
+    serde::Deserialize,
+    serde::Serialize,
+)]
 pub enum RoomType {
     /// room player is moved too when dungeon generation finishes
     DungeonStart,
-    // TODO: when killed make portal/something too trigger next zone
-    /// final room in dungeon. 1 HARD enemy.
+    /// final room in dungeon. 1 HARD enemy, opens the floor portal when killed
     DungeonEnd,
     /// room has special functions in dungeon
     Special,
@@ -311,10 +555,23 @@ pub enum RoomType {
     Hideout,
     /// small unique non ending bosses
     MiniBoss,
+    /// hidden room attached too another room, its exit is covered by a breakable wall
+    Secret,
 }
 
 /// what size/shape is this room
-#[derive(Debug, Clone, Reflect, PartialEq, Eq, PartialOrd, Default, Ord)]
+#[derive(
+    Debug,
+    Clone,
+    Reflect,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Default,
+    Ord,
+    serde::Deserialize,
+    serde::Serialize,
+)]
 pub enum RoomShape {
     /// shape doesnt fit below definitions.
     NonStandard,
@@ -336,6 +593,104 @@ pub enum RoomShape {
     /// 256 tile x 512 tile
     HugeLong,
 }
+
+/// how a room preset is turned when placed in a dungeon,
+/// rotations are counter clockwise
+#[derive(
+    Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash, Default, serde::Deserialize, serde::Serialize,
+)]
+pub enum RoomOrientation {
+    /// placed how it was made in ldtk
+    #[default]
+    Identity,
+    /// turned a quarter turn
+    Rotate90,
+    /// turned upside down
+    Rotate180,
+    /// turned three quarter turns
+    Rotate270,
+    /// flipped left too right
+    MirrorX,
+    /// flipped top too bottom
+    MirrorY,
+}
+
+impl RoomOrientation {
+    /// gets orientation from its `IdentOrientation` ldtk enum value
+    pub fn from_ldtk(value: &str) -> Option<Self> {
+        match value {
+            "Identity" => Some(Self::Identity),
+            "Rotate90" => Some(Self::Rotate90),
+            "Rotate180" => Some(Self::Rotate180),
+            "Rotate270" => Some(Self::Rotate270),
+            "MirrorX" => Some(Self::MirrorX),
+            "MirrorY" => Some(Self::MirrorY),
+            _ => None,
+        }
+    }
+
+    /// true if the rooms 3/4 view wall art and entity sprites still look right
+    /// after `room_transform` turns the level, only left right flips do.
+    /// rooms are only ever placed with these orientations
+    pub const fn keeps_art_upright(self) -> bool {
+        matches!(self, Self::Identity | Self::MirrorX)
+    }
+
+    /// true if room width and height are swapped by this orientation
+    pub const fn swaps_axes(self) -> bool {
+        matches!(self, Self::Rotate90 | Self::Rotate270)
+    }
+
+    /// size of a room with `size` after being turned
+    pub const fn oriented_size(self, size: IVec2) -> IVec2 {
+        if self.swaps_axes() {
+            IVec2::new(size.y, size.x)
+        } else {
+            size
+        }
+    }
+
+    /// where `tile` of a room `size` tiles big ends up after being turned
+    pub const fn orient_tile(self, tile: IVec2, size: IVec2) -> IVec2 {
+        let last_x = size.x - 1;
+        let last_y = size.y - 1;
+        match self {
+            Self::Identity => tile,
+            Self::Rotate90 => IVec2::new(last_y - tile.y, tile.x),
+            Self::Rotate180 => IVec2::new(last_x - tile.x, last_y - tile.y),
+            Self::Rotate270 => IVec2::new(tile.y, last_x - tile.x),
+            Self::MirrorX => IVec2::new(last_x - tile.x, tile.y),
+            Self::MirrorY => IVec2::new(tile.x, last_y - tile.y),
+        }
+    }
+
+    /// how far the level origin is moved from the rooms bottom left corner,
+    /// `oriented_size` is the turned room size in px
+    pub const fn pivot_offset(self, oriented_size: Vec2) -> Vec2 {
+        match self {
+            Self::Identity => Vec2::ZERO,
+            Self::Rotate90 | Self::MirrorX => Vec2::new(oriented_size.x, 0.0),
+            Self::Rotate180 => oriented_size,
+            Self::Rotate270 | Self::MirrorY => Vec2::new(0.0, oriented_size.y),
+        }
+    }
+
+    /// transform that turns a level so its tiles, colliders and entitys
+    /// fill the rect starting at `min` with `oriented_size`.
+    /// this turns child sprites too, see `keeps_art_upright`
+    pub fn room_transform(self, min: Vec2, oriented_size: Vec2) -> Transform {
+        let transform =
+            Transform::from_translation((min + self.pivot_offset(oriented_size)).extend(0.0));
+        match self {
+            Self::Identity => transform,
+            Self::Rotate90 => transform.with_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+            Self::Rotate180 => transform.with_rotation(Quat::from_rotation_z(PI)),
+            Self::Rotate270 => transform.with_rotation(Quat::from_rotation_z(-FRAC_PI_2)),
+            Self::MirrorX => transform.with_scale(Vec3::new(-1.0, 1.0, 1.0)),
+            Self::MirrorY => transform.with_scale(Vec3::new(1.0, -1.0, 1.0)),
+        }
+    }
+}
 ///  returns `Some(RoomShape)` if field exists in `field_instances` else `None`
 pub fn try_get_roomshape(field_instances: &[FieldInstance]) -> Option<RoomShape> {
     let room_ident = field_instances
@@ -378,6 +733,22 @@ pub fn try_get_roomlevel(field_instances: &[FieldInstance]) -> Option<RoomLevel>
     }
 }
 
+/// returns `RoomBiome` from the `IdentBiome` field of `field_instances`,
+/// `RoomBiome::default()` if the field is missing. `Err` holds the value if it is not a known biome
+pub fn try_get_roombiome(field_instances: &[FieldInstance]) -> Result<RoomBiome, String> {
+    let Some(room_ident) = field_instances
+        .iter()
+        .find(|f| f.identifier == "IdentBiome")
+    else {
+        return Ok(RoomBiome::default());
+    };
+    let FieldValue::Enum(Some(enum_value)) = &room_ident.value else {
+        return Err(format!("{:?}", room_ident.value));
+    };
+
+    RoomBiome::from_ldtk(enum_value).ok_or_else(|| enum_value.clone())
+}
+
 /// returns `Some(RoomType)` if field exists in in `field_instances` else `None`
 pub fn try_get_roomtype(field_instances: &[FieldInstance]) -> Option<RoomType> {
     let room_ident = field_instances
@@ -394,9 +765,37 @@ pub fn try_get_roomtype(field_instances: &[FieldInstance]) -> Option<RoomType> {
         "Normal" => Some(RoomType::Normal),
         "Hideout" => Some(RoomType::Hideout),
         "MiniBoss" => Some(RoomType::MiniBoss),
+        "Secret" => Some(RoomType::Secret),
         e => {
             warn!("unknown room type {:?}", e);
             None
         }
     }
 }
+
+/// returns every `RoomOrientation` listed in the `IdentOrientations` field of `field_instances`,
+/// `Err` holds the first value that is not a known orientation.
+/// `RoomOrientation::Identity` is always included
+pub fn try_get_roomorientations(
+    field_instances: &[FieldInstance],
+) -> Result<Vec<RoomOrientation>, String> {
+    let mut orientations = vec![RoomOrientation::Identity];
+    let Some(room_ident) = field_instances
+        .iter()
+        .find(|f| f.identifier == "IdentOrientations")
+    else {
+        return Ok(orientations);
+    };
+    let FieldValue::Enums(values) = &room_ident.value else {
+        return Err(format!("{:?}", room_ident.value));
+    };
+
+    for value in values.iter().flatten() {
+        let orientation = RoomOrientation::from_ldtk(value).ok_or_else(|| value.clone())?;
+        if !orientations.contains(&orientation) {
+            orientations.push(orientation);
+        }
+    }
+
+    Ok(orientations)
+}
@@ -0,0 +1,627 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use bevy::{color::ColorToPacked, prelude::*};
+use bevy_ecs_ldtk::ldtk::{LdtkJson, Level};
+use clap::Parser;
+use image::{ImageBuffer, Rgba};
+use rand::Rng;
+
+use crate::{
+    colors,
+    game::game_world::dungeonator_v2::{
+        components::{
+            DungeonRng, DungeonRoomDatabase, DungeonSettings, HallwayPathCosts, HallwayWidths,
+            RoomBiome, RoomBlueprint, RoomDistribution, RoomLevel, RoomOrientation, RoomShape,
+            RoomType,
+        },
+        dungeon_origin, floors,
+        hallways::{hallway_brush, HallWayBlueprint},
+        layout::{self, DungeonLayout},
+        room_graph::rules::PlacementRule,
+        tile_graph::{r#impl::world_cell, TileGraph},
+    },
+    loading::{custom_assets::floor_definitions::FloorTable, registry::RegistryIdentifier},
+};
+
+/// folder layouts are exported too if no folder is given
+pub const DEFAULT_EXPORT_FOLDER: &str = "layouts";
+
+/// size of each tile in exported map images, in pixels
+const EXPORT_TILE_PX: i32 = 4;
+/// empty tiles around the dungeon in exported map images
+const EXPORT_MARGIN_TILES: i32 = 4;
+/// how many pixels each pixel of a room label font is
+const LABEL_SCALE: i32 = 3;
+
+/// 3x5 pixel font for room labels, each row is 3 bits with the left pixel as the highest bit
+const DIGIT_FONT: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// finished dungeon layout in a form that can be saved and compared between generator changes.
+/// added too the dungeon root when its layout is created so the console can export it
+#[derive(Debug, Clone, Component, serde::Serialize)]
+pub struct LayoutExport {
+    /// seed the dungeon was generated from
+    pub seed: u64,
+    /// floor of the run this dungeon was made for, first floor is 0
+    pub floor: usize,
+    /// dungeon position in world, every other position is relative too this
+    pub origin: [f32; 2],
+    /// where the player is placed when the dungeon is entered
+    pub start_position: [f32; 2],
+    /// settings used too create this layout
+    pub settings: SettingsExport,
+    /// placed rooms sorted by id
+    pub rooms: Vec<RoomExport>,
+    /// hallways between room exits
+    pub hallways: Vec<HallwayExport>,
+}
+
+/// generator settings saved with a layout
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SettingsExport {
+    /// final dungeon size in tiles
+    pub size: [u32; 2],
+    /// border around outside of dungeon in tiles
+    pub border: u32,
+    /// minimum space between rooms in tiles
+    pub tiles_between_rooms: u32,
+    /// amount of rooms that were requested
+    pub distribution: RoomDistribution,
+    /// percentage of paths between rooms that are chosen to loop
+    pub hallway_loop_chance: f32,
+    /// room levels that could be chosen
+    pub room_levels: Vec<RoomLevel>,
+    /// theme rooms and hallways were chosen from
+    pub biome: RoomBiome,
+    /// bosses that can replace the end room boss
+    pub boss_pool: Vec<RegistryIdentifier>,
+    /// rules the room graph had too follow
+    pub placement_rules: Vec<PlacementRule>,
+    /// costs used when routing hallways
+    pub path_costs: HallwayPathCosts,
+    /// how wide hallways are built
+    pub hallway_widths: HallwayWidths,
+}
+
+/// room saved with a layout
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RoomExport {
+    /// rooms unique number, also its label in the map image
+    pub id: u32,
+    /// name of the preset this room was created from
+    pub name: String,
+    /// what function this room serves for the dungeon
+    pub rtype: RoomType,
+    /// room shape
+    pub shape: RoomShape,
+    /// rooms level
+    pub level: RoomLevel,
+    /// rooms biome
+    pub biome: RoomBiome,
+    /// how the rooms preset was turned when placed
+    pub orientation: RoomOrientation,
+    /// bottom left corner of room in px
+    pub position: [i32; 2],
+    /// size of room in px
+    pub size: [i32; 2],
+    /// bottom left corner of each exit tile in px
+    pub exits: Vec<[i32; 2]>,
+}
+
+/// hallway saved with a layout
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HallwayExport {
+    /// ids of the rooms this hallway connects
+    pub rooms: [u32; 2],
+    /// exit this hallway starts at
+    pub start: [i32; 2],
+    /// exit this hallway ends at
+    pub end: [i32; 2],
+    /// hallway width in tiles
+    pub width: u32,
+    /// tile positions where the hallway starts, turns and ends.
+    /// empty if no path could be found for this hallway
+    pub path: Vec<[f32; 2]>,
+}
+
+/// reasons a layout could not be exported
+#[derive(Debug)]
+pub enum ExportError {
+    /// file could not be read or written
+    Io(std::io::Error),
+    /// layout or ldtk file could not be converted from/too json
+    Json(serde_json::Error),
+    /// floor table could not be parsed
+    Ron(ron::error::SpannedError),
+    /// map image could not be saved
+    Image(image::ImageError),
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "file error: {e}"),
+            Self::Json(e) => write!(f, "json error: {e}"),
+            Self::Ron(e) => write!(f, "ron error: {e}"),
+            Self::Image(e) => write!(f, "image error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for ExportError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        Self::Ron(value)
+    }
+}
+
+impl From<image::ImageError> for ExportError {
+    fn from(value: image::ImageError) -> Self {
+        Self::Image(value)
+    }
+}
+
+impl LayoutExport {
+    /// creates export for `layout` with the dungeon placed at `origin`
+    pub fn from_layout(layout: &DungeonLayout, origin: Vec2) -> Self {
+        let settings = &layout.settings;
+        let mut rooms: Vec<RoomExport> = layout.rooms.iter().map(RoomExport::from).collect();
+        rooms.sort_by_key(|f| f.id);
+
+        Self {
+            seed: settings.seed.0,
+            floor: settings.floor,
+            origin: origin.to_array(),
+            start_position: layout.start_position.to_array(),
+            settings: SettingsExport::from(settings),
+            rooms,
+            hallways: layout
+                .hallways
+                .iter()
+                .map(|hallway| HallwayExport {
+                    rooms: [hallway.connected_rooms.0 .0, hallway.connected_rooms.1 .0],
+                    start: hallway.start_pos.to_array(),
+                    end: hallway.end_pos.to_array(),
+                    width: hallway.width,
+                    path: hallway_polyline(settings, &layout.tile_graph, hallway),
+                })
+                .collect(),
+        }
+    }
+
+    /// file name for this layout without an extension
+    pub fn file_stem(&self) -> String {
+        format!("floor{}_seed{}", self.floor, self.seed)
+    }
+
+    /// writes layout json and map image into `folder`, returns paths of the json and image
+    ///
+    /// # Errors
+    /// returns `ExportError` if either file could not be written
+    pub fn write_files(&self, folder: &Path) -> Result<[PathBuf; 2], ExportError> {
+        std::fs::create_dir_all(folder)?;
+        let json_path = folder.join(self.file_stem()).with_extension("json");
+        let image_path = folder.join(self.file_stem()).with_extension("png");
+
+        self.write_json(&json_path)?;
+        self.write_image(&image_path)?;
+        Ok([json_path, image_path])
+    }
+
+    /// writes layout too `path` as pretty json
+    ///
+    /// # Errors
+    /// returns `ExportError` if file could not be created or layout could not be serialized
+    pub fn write_json(&self, path: &Path) -> Result<(), ExportError> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// draws map of layout and saves it too `path`.
+    /// rooms are colored by type and labeled with there id, hallways are drawn over empty space
+    ///
+    /// # Errors
+    /// returns `ExportError` if image could not be saved
+    pub fn write_image(&self, path: &Path) -> Result<(), ExportError> {
+        let room_cells = |room: &RoomExport| {
+            let position = IVec2::from_array(room.position).as_vec2();
+            let size = IVec2::from_array(room.size).as_vec2();
+            IRect::from_corners(world_cell(position), world_cell(position + size))
+        };
+        let bounds = self
+            .rooms
+            .iter()
+            .map(room_cells)
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default();
+        let mut canvas = MapCanvas::new(bounds);
+
+        for room in &self.rooms {
+            let cells = room_cells(room);
+            for x in cells.min.x..cells.max.x {
+                for y in cells.min.y..cells.max.y {
+                    let edge = x == cells.min.x
+                        || y == cells.min.y
+                        || x == cells.max.x - 1
+                        || y == cells.max.y - 1;
+                    let color = if edge {
+                        colors::BLACK
+                    } else {
+                        room_color(&room.rtype)
+                    };
+                    canvas.paint_cell(IVec2 { x, y }, color);
+                }
+            }
+        }
+
+        let room_rects: Vec<IRect> = self.rooms.iter().map(room_cells).collect();
+        let inside_room = |cell: IVec2| {
+            room_rects
+                .iter()
+                .any(|f| f.min.cmple(cell).all() && cell.cmplt(f.max).all())
+        };
+        for hallway in &self.hallways {
+            for segment in hallway.path.windows(2) {
+                let end = world_cell(Vec2::from_array(segment[1]));
+                let mut cell = world_cell(Vec2::from_array(segment[0]));
+                loop {
+                    // path is drawn into room exits, wider parts stop at room edges
+                    for offset in hallway_brush(hallway.width) {
+                        let widened = cell + offset;
+                        if offset == IVec2::ZERO || !inside_room(widened) {
+                            canvas.paint_cell(widened, colors::KHAKI);
+                        }
+                    }
+                    if cell == end {
+                        break;
+                    }
+                    cell += (end - cell).signum();
+                }
+            }
+        }
+
+        for exit in self.rooms.iter().flat_map(|f| &f.exits) {
+            canvas.paint_cell(world_cell(IVec2::from_array(*exit).as_vec2()), colors::RED);
+        }
+        canvas.paint_cell(
+            world_cell(Vec2::from_array(self.start_position)),
+            colors::MAGENTA,
+        );
+
+        for room in &self.rooms {
+            let center = IVec2::from_array(room.position) + IVec2::from_array(room.size) / 2;
+            let label_center =
+                canvas.cell_pixel(world_cell(center.as_vec2())) + IVec2::splat(EXPORT_TILE_PX / 2);
+            canvas.paint_label(label_center, room.id, colors::WHITE);
+        }
+
+        canvas.image.save(path)?;
+        Ok(())
+    }
+}
+
+impl From<&DungeonSettings> for SettingsExport {
+    fn from(settings: &DungeonSettings) -> Self {
+        Self {
+            size: [settings.size.x, settings.size.y],
+            border: settings.border,
+            tiles_between_rooms: settings.tiles_between_rooms,
+            distribution: settings.distribution.clone(),
+            hallway_loop_chance: settings.hallway_loop_chance,
+            room_levels: settings.room_levels.clone(),
+            biome: settings.biome,
+            boss_pool: settings.boss_pool.clone(),
+            placement_rules: settings.placement_rules.clone(),
+            path_costs: settings.path_costs,
+            hallway_widths: settings.hallway_widths.clone(),
+        }
+    }
+}
+
+impl From<&RoomBlueprint> for RoomExport {
+    fn from(room: &RoomBlueprint) -> Self {
+        Self {
+            id: room.id.0,
+            name: room.name.clone(),
+            rtype: room.descriptor.rtype.clone(),
+            shape: room.descriptor.shape.clone(),
+            level: room.descriptor.level.clone(),
+            biome: room.descriptor.biome,
+            orientation: room.orientation,
+            position: room.room_space.min.to_array(),
+            size: room.room_space.size().to_array(),
+            exits: room.exits.iter().map(|f| f.position.to_array()).collect(),
+        }
+    }
+}
+
+/// tile positions where `hallway` starts, changes direction and ends
+fn hallway_polyline(
+    settings: &DungeonSettings,
+    tile_graph: &TileGraph,
+    hallway: &HallWayBlueprint,
+) -> Vec<[f32; 2]> {
+    let tiles: Vec<Vec2> = hallway
+        .node_path
+        .iter()
+        .filter_map(|f| tile_graph.node_weight(*f))
+        .map(|f| tile_graph.get_tiles_translation_world(settings, f.tile))
+        .collect();
+
+    tiles
+        .iter()
+        .enumerate()
+        .filter(|(i, tile)| {
+            let (Some(previous), Some(next)) = (
+                i.checked_sub(1).and_then(|f| tiles.get(f)),
+                tiles.get(i + 1),
+            ) else {
+                return true;
+            };
+            **tile - *previous != *next - **tile
+        })
+        .map(|(_, tile)| tile.to_array())
+        .collect()
+}
+
+/// color rooms of `rtype` are filled with in map images
+const fn room_color(rtype: &RoomType) -> Srgba {
+    match rtype {
+        RoomType::DungeonStart => colors::LIME_GREEN,
+        RoomType::DungeonEnd => colors::CRIMSON,
+        RoomType::Special => colors::GOLD,
+        RoomType::Normal => colors::STEEL_BLUE,
+        RoomType::Hideout => colors::SLATE_GRAY,
+        RoomType::MiniBoss => colors::DARK_ORANGE,
+        RoomType::Secret => colors::ORCHID,
+    }
+}
+
+/// map image of a layout, each tile is a `EXPORT_TILE_PX` square
+struct MapCanvas {
+    /// image being drawn
+    image: ImageBuffer<Rgba<u8>, Vec<u8>>,
+    /// tile cells covered by the layout
+    bounds: IRect,
+}
+
+impl MapCanvas {
+    /// creates empty canvas large enough for `bounds` and its margin
+    fn new(bounds: IRect) -> Self {
+        let size = (bounds.size() + IVec2::splat(EXPORT_MARGIN_TILES * 2)) * EXPORT_TILE_PX;
+        Self {
+            image: ImageBuffer::from_pixel(
+                size.x.max(1) as u32,
+                size.y.max(1) as u32,
+                Rgba(colors::DARK_SLATE_GRAY.to_u8_array()),
+            ),
+            bounds,
+        }
+    }
+
+    /// top left pixel of `cell`, images start at the top so y is flipped
+    fn cell_pixel(&self, cell: IVec2) -> IVec2 {
+        IVec2 {
+            x: cell.x - self.bounds.min.x + EXPORT_MARGIN_TILES,
+            y: self.bounds.max.y - 1 - cell.y + EXPORT_MARGIN_TILES,
+        } * EXPORT_TILE_PX
+    }
+
+    /// colors a single pixel, pixels outside the image are ignored
+    fn paint_pixel(&mut self, pixel: IVec2, color: Srgba) {
+        let (Ok(x), Ok(y)) = (u32::try_from(pixel.x), u32::try_from(pixel.y)) else {
+            return;
+        };
+        if let Some(image_pixel) = self.image.get_pixel_mut_checked(x, y) {
+            *image_pixel = Rgba(color.to_u8_array());
+        }
+    }
+
+    /// colors every pixel of `cell`
+    fn paint_cell(&mut self, cell: IVec2, color: Srgba) {
+        self.paint_square(self.cell_pixel(cell), EXPORT_TILE_PX, color);
+    }
+
+    /// colors `size` x `size` square with `corner` as its top left pixel
+    fn paint_square(&mut self, corner: IVec2, size: i32, color: Srgba) {
+        for x in 0..size {
+            for y in 0..size {
+                self.paint_pixel(corner + IVec2 { x, y }, color);
+            }
+        }
+    }
+
+    /// draws `number` centered on `center` pixel
+    fn paint_label(&mut self, center: IVec2, number: u32, color: Srgba) {
+        let digits: Vec<usize> = number
+            .to_string()
+            .bytes()
+            .map(|f| usize::from(f - b'0'))
+            .collect();
+        // glyphs are 3 wide with 1 pixel between them
+        let glyph_advance = 4 * LABEL_SCALE;
+        let label_size = IVec2 {
+            x: digits.len() as i32 * glyph_advance - LABEL_SCALE,
+            y: 5 * LABEL_SCALE,
+        };
+        let top_left = center - label_size / 2;
+
+        for (index, digit) in digits.iter().enumerate() {
+            for (row, bits) in DIGIT_FONT[*digit].iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+                    let font_pixel = top_left
+                        + IVec2 {
+                            x: index as i32 * glyph_advance + column * LABEL_SCALE,
+                            y: row as i32 * LABEL_SCALE,
+                        };
+                    self.paint_square(font_pixel, LABEL_SCALE, color);
+                }
+            }
+        }
+    }
+}
+
+/// arguments for exporting dungeon layouts without starting the game
+#[derive(Debug, Parser)]
+#[command(
+    name = "export-layout",
+    about = "exports dungeon layouts as json and png without starting the game"
+)]
+pub struct HeadlessExportArgs {
+    /// folder too write layouts into
+    #[arg(long, default_value = DEFAULT_EXPORT_FOLDER)]
+    pub out: PathBuf,
+    /// run seed, each floor uses this seed offset by its floor number. random if not set
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// only export this floor, first floor is 0. every floor is exported if not set
+    #[arg(long)]
+    pub floor: Option<usize>,
+    /// ldtk project rooms are loaded from
+    #[arg(
+        long,
+        default_value = "assets/packs/asha/levels/aspenhallsLevelSet.ldtk"
+    )]
+    pub levels: PathBuf,
+    /// floor table floor settings are loaded from
+    #[arg(long, default_value = "assets/packs/asha/levels/asha.floors.ron")]
+    pub floors: PathBuf,
+}
+
+/// parses `args` and exports the requested floors without creating an app,
+/// layouts match what the game creates for the same seed and floor
+pub fn run_headless_export(args: impl IntoIterator<Item = String>) -> ExitCode {
+    let args = match HeadlessExportArgs::try_parse_from(args) {
+        Ok(args) => args,
+        Err(e) => e.exit(),
+    };
+
+    let room_database = match load_room_database(&args.levels) {
+        Ok(database) => database,
+        Err(e) => {
+            eprintln!("could not load rooms from {}: {e}", args.levels.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let floor_table = match load_floor_table(&args.floors) {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("could not load floors from {}: {e}", args.floors.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let run_seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let floor_range = args
+        .floor
+        .map_or(0..floor_table.floors.len().max(1), |floor| floor..floor + 1);
+
+    let mut failed = false;
+    for floor in floor_range {
+        let seed = floors::floor_seed(run_seed, floor);
+        let definition = floors::floor_definition(Some(&floor_table), floor);
+        let settings = floors::floor_settings(definition, floor, seed);
+
+        // pull from rng in the same order as `handle_generate_requests` so layouts match the game
+        let mut rng = DungeonRng::from_seed(seed);
+        let origin = dungeon_origin(&mut rng.0);
+
+        let result = layout::generate_layout(&settings, &room_database, &mut rng.0)
+            .map_err(|e| e.to_string())
+            .and_then(|layout| {
+                LayoutExport::from_layout(&layout, origin)
+                    .write_files(&args.out)
+                    .map_err(|e| e.to_string())
+            });
+
+        match result {
+            Ok([json, image]) => println!(
+                "floor {floor} (seed {seed}): wrote {} and {}",
+                json.display(),
+                image.display()
+            ),
+            Err(e) => {
+                eprintln!("floor {floor} (seed {seed}): {e}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// reads ldtk project at `project_path` and its external levels into a `DungeonRoomDatabase`,
+/// invalid levels are skipped and printed
+fn load_room_database(project_path: &Path) -> Result<DungeonRoomDatabase, ExportError> {
+    let project: LdtkJson = serde_json::from_str(&std::fs::read_to_string(project_path)?)?;
+    let project_folder = project_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut database = DungeonRoomDatabase::default();
+    let levels = project
+        .levels
+        .iter()
+        .chain(project.worlds.iter().flat_map(|f| &f.levels));
+
+    for level in levels {
+        let added = match &level.external_rel_path {
+            Some(level_path) => {
+                let level_file = std::fs::read_to_string(project_folder.join(level_path))?;
+                let external_level: Level = serde_json::from_str(&level_file)?;
+                database.add_level(&project, &external_level)
+            }
+            None => database.add_level(&project, level),
+        };
+
+        if let Err(issues) = added {
+            for issue in issues {
+                eprintln!("skipping invalid room level {issue}");
+            }
+        }
+    }
+
+    for category in database.missing_categories() {
+        eprintln!("room database has no {category}, dungeons can not be generated");
+    }
+
+    Ok(database)
+}
+
+/// reads floor table at `path`
+fn load_floor_table(path: &Path) -> Result<FloorTable, ExportError> {
+    Ok(ron::from_str(&std::fs::read_to_string(path)?)?)
+}
@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::TilemapSize;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    game::{
+        characters::{components::CharacterType, player::PlayerSelectedHero},
+        game_world::{
+            components::{ActorTeleportEvent, SpawnerWave},
+            dungeonator_v2::{
+                components::{Dungeon, DungeonRng, DungeonSeed, DungeonSettings, HallwayPathCosts},
+                generation::{DungeonGenerationFailed, GenerateDungeon, GenerationPhase},
+                teleport_player_too_start_location, GeneratorState,
+            },
+            DungeonRunFinishedEvent,
+        },
+    },
+    loading::{
+        assets::AspenLevelsetHandles,
+        config::{DifficultySettings, GeneralSettings},
+        custom_assets::floor_definitions::{FloorDefinition, FloorTable},
+        registry::ActorRegistry,
+    },
+};
+
+/// progress through the current dungeon run
+#[derive(Debug, Clone, Copy, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct DungeonRun {
+    /// floor currently being played, first floor is 0
+    pub floor: usize,
+    /// how many floors this run has
+    pub floor_count: usize,
+}
+
+impl DungeonRun {
+    /// is the current floor the final floor of this run
+    pub const fn on_last_floor(&self) -> bool {
+        self.floor + 1 >= self.floor_count
+    }
+}
+
+/// dungeon the player is playing,
+/// other dungeons are floors generated ahead of time and stay hidden
+#[derive(Debug, Clone, Copy, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct ActiveDungeon;
+
+/// amount of floors in a run, limited by `max_dungeon_amount`
+pub fn run_length(floor_table: Option<&FloorTable>, difficulty: &DifficultySettings) -> usize {
+    let table_floors = floor_table.map_or(1, |table| table.floors.len().max(1));
+    let max_floors = usize::try_from(difficulty.max_dungeon_amount.max(1)).unwrap_or(1);
+
+    table_floors.min(max_floors)
+}
+
+/// gets definition for `floor` from `floor_table`,
+/// last floor is reused if `floor` is past the end of the table
+pub fn floor_definition(floor_table: Option<&FloorTable>, floor: usize) -> FloorDefinition {
+    let Some(table) = floor_table else {
+        warn!("floor table is not loaded, using default floor");
+        return FloorDefinition::default();
+    };
+
+    table
+        .floors
+        .get(floor)
+        .or_else(|| table.floors.last())
+        .cloned()
+        .unwrap_or_else(|| {
+            warn!("floor table has no floors, using default floor");
+            FloorDefinition::default()
+        })
+}
+
+/// seed used for `floor` of a run started with `run_seed`, each floor gets a different seed
+pub const fn floor_seed(run_seed: u64, floor: usize) -> DungeonSeed {
+    DungeonSeed(run_seed.wrapping_add(floor as u64))
+}
+
+/// creates `DungeonSettings` for `floor` from its `definition`
+pub fn floor_settings(
+    definition: FloorDefinition,
+    floor: usize,
+    seed: DungeonSeed,
+) -> DungeonSettings {
+    DungeonSettings {
+        seed,
+        floor,
+        room_levels: definition.room_levels,
+        biome: definition.biome,
+        hallway_tileset: definition.hallway_tileset,
+        boss_pool: definition.boss_pool,
+        border: definition.border,
+        // room placing settings
+        size: TilemapSize {
+            x: definition.size.x,
+            y: definition.size.y,
+        },
+        tiles_between_rooms: definition.tiles_between_rooms,
+        distribution: definition.distribution,
+        // hallway placing settings/data
+        hallway_loop_chance: definition.hallway_loop_chance,
+        path_costs: HallwayPathCosts::default(),
+        hallway_widths: definition.hallway_widths,
+        placement_rules: definition.placement_rules,
+    }
+}
+
+/// creates a `GenerateDungeon` request for `floor` of the run.
+/// configured seeds are offset by floor so each floor is different
+fn floor_request(
+    floor_table: Option<&FloorTable>,
+    general_settings: &GeneralSettings,
+    floor: usize,
+) -> GenerateDungeon {
+    let seed = general_settings.dungeon_seed.map_or_else(
+        || DungeonSeed(rand::thread_rng().gen()),
+        |seed| floor_seed(seed, floor),
+    );
+    info!("floor {} seed: {seed}", floor + 1);
+
+    GenerateDungeon {
+        settings: floor_settings(floor_definition(floor_table, floor), floor, seed),
+        seed,
+    }
+}
+
+/// requests a dungeon for the current floor of the `DungeonRun`,
+/// uses a dungeon generated ahead of time if one exists for this floor
+pub fn request_floor_dungeon(
+    mut cmds: Commands,
+    general_settings: Res<GeneralSettings>,
+    difficulty: Res<DifficultySettings>,
+    ldtk_project_handles: Res<AspenLevelsetHandles>,
+    floor_tables: Res<Assets<FloorTable>>,
+    mut run: ResMut<DungeonRun>,
+    dungeons: Query<(Entity, &Dungeon, Has<ActiveDungeon>)>,
+    mut requests: EventWriter<GenerateDungeon>,
+) {
+    let floor_table = floor_tables.get(&ldtk_project_handles.floor_table);
+    run.floor_count = run_length(floor_table, &difficulty);
+    info!("creating floor {} of {}", run.floor + 1, run.floor_count);
+
+    let configured_seed = general_settings
+        .dungeon_seed
+        .map(|seed| floor_seed(seed, run.floor));
+    let mut pregenerated = false;
+    for (dungeon_id, dungeon, is_active) in &dungeons {
+        let floor = dungeon.settings.floor;
+        let usable = !is_active
+            && !pregenerated
+            && floor == run.floor
+            && configured_seed.is_none_or(|seed| seed == dungeon.settings.seed);
+
+        if usable {
+            info!(
+                "using dungeon generated ahead of time for floor {}",
+                floor + 1
+            );
+            pregenerated = true;
+        } else if is_active || floor <= run.floor {
+            // finished floors are removed when the floor portal is entered,
+            // restarted floors and stale floors are removed here
+            cmds.entity(dungeon_id).despawn_recursive();
+        }
+    }
+
+    if !pregenerated {
+        requests.send(floor_request(floor_table, &general_settings, run.floor));
+    }
+}
+
+/// moves player into the current floors dungeon once it is generated
+/// and starts generating the next floor in the background
+#[allow(clippy::too_many_arguments)]
+pub fn enter_floor_dungeon(
+    mut cmds: Commands,
+    general_settings: Res<GeneralSettings>,
+    ldtk_project_handles: Res<AspenLevelsetHandles>,
+    floor_tables: Res<Assets<FloorTable>>,
+    mut run: ResMut<DungeonRun>,
+    mut dungeons: Query<
+        (
+            Entity,
+            &Dungeon,
+            &GenerationPhase,
+            &Transform,
+            &mut Visibility,
+        ),
+        Without<ActiveDungeon>,
+    >,
+    player_query: Query<Entity, With<PlayerSelectedHero>>,
+    mut failed_events: EventReader<DungeonGenerationFailed>,
+    mut tp_events: EventWriter<ActorTeleportEvent>,
+    mut requests: EventWriter<GenerateDungeon>,
+    mut run_finished: EventWriter<DungeonRunFinishedEvent>,
+) {
+    let failed: Vec<Entity> = failed_events.read().map(|f| f.entity).collect();
+    let current_floor = dungeons
+        .iter()
+        .filter(|(dungeon_id, dungeon, ..)| {
+            dungeon.settings.floor == run.floor && !failed.contains(dungeon_id)
+        })
+        .map(|(dungeon_id, _, phase, ..)| (dungeon_id, *phase))
+        .collect::<Vec<_>>();
+
+    if current_floor.is_empty() {
+        if !failed.is_empty() {
+            error!("floor {} could not be generated, ending run", run.floor + 1);
+            run_finished.send(DungeonRunFinishedEvent {
+                floors_cleared: run.floor,
+            });
+            *run = DungeonRun::default();
+            cmds.insert_resource(NextState::Pending(GeneratorState::NoDungeon));
+        }
+        return;
+    }
+
+    let Some((dungeon_id, _)) = current_floor
+        .iter()
+        .find(|(_, phase)| *phase == GenerationPhase::Ready)
+    else {
+        return;
+    };
+    let (dungeon_id, dungeon, _, dungeon_transform, mut visibility) = dungeons
+        .get_mut(*dungeon_id)
+        .expect("dungeon was found in query");
+
+    info!("entering floor {}", run.floor + 1);
+    cmds.entity(dungeon_id).insert(ActiveDungeon);
+    *visibility = Visibility::Visible;
+    teleport_player_too_start_location(
+        dungeon_transform.translation.truncate() + dungeon.start_position,
+        &player_query,
+        &mut tp_events,
+    );
+    cmds.insert_resource(NextState::Pending(GeneratorState::FinishedDungeonGen));
+
+    let next_floor = run.floor + 1;
+    let next_floor_requested = dungeons
+        .iter()
+        .any(|(_, dungeon, ..)| dungeon.settings.floor == next_floor);
+    if !run.on_last_floor() && !next_floor_requested {
+        info!("generating floor {} ahead of time", next_floor + 1);
+        requests.send(floor_request(
+            floor_tables.get(&ldtk_project_handles.floor_table),
+            &general_settings,
+            next_floor,
+        ));
+    }
+}
+
+/// swaps bosses in newly spawned spawner waves for bosses from the pool of the dungeon they are in
+pub fn apply_floor_boss_pool(
+    registry: Res<ActorRegistry>,
+    mut dungeon_root: Query<(&Dungeon, &mut DungeonRng)>,
+    mut new_waves: Query<(Entity, &mut SpawnerWave), Added<SpawnerWave>>,
+    parents: Query<&Parent>,
+) {
+    for (wave_entity, mut wave) in &mut new_waves {
+        let Some((dungeon, mut rng)) = parents
+            .iter_ancestors(wave_entity)
+            .find(|f| dungeon_root.contains(*f))
+            .and_then(|f| dungeon_root.get_mut(f).ok())
+        else {
+            continue;
+        };
+
+        let boss_pool: Vec<_> = dungeon
+            .settings
+            .boss_pool
+            .iter()
+            .filter(|f| {
+                let is_boss =
+                    registry.characters.get_character_type(f) == Some(CharacterType::Boss);
+                if !is_boss {
+                    warn!("boss pool entry {:?} is not a boss", f);
+                }
+                is_boss
+            })
+            .collect();
+
+        if boss_pool.is_empty() {
+            continue;
+        }
+
+        for identifier in &mut wave.too_spawn {
+            if registry.characters.get_character_type(identifier) != Some(CharacterType::Boss) {
+                continue;
+            }
+            if let Some(boss) = boss_pool.choose(&mut rng.0) {
+                info!("floor {} boss: {:?}", dungeon.settings.floor, boss);
+                identifier.clone_from(boss);
+            }
+        }
+    }
+}
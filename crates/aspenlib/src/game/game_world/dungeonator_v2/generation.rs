@@ -0,0 +1,408 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+};
+use bevy_ecs_ldtk::{
+    prelude::{LayerMetadata, LdtkProject},
+    LevelEvent, LevelIid,
+};
+use rand::rngs::StdRng;
+
+use crate::{
+    game::game_world::dungeonator_v2::{
+        components::{
+            Dungeon, DungeonContainerBundle, DungeonRng, DungeonRoomDatabase, DungeonSeed,
+            DungeonSettings, RoomBlueprint,
+        },
+        dungeon_bounds, dungeon_origin, free_dungeon_origin,
+        hallways::create_hallway_layer,
+        layout::{self, DungeonLayout, LayoutError},
+        room_graph::RoomGraph,
+        spawn_layout,
+        tile_graph::TileGraph,
+    },
+    loading::assets::AspenLevelsetHandles,
+};
+
+/// request too generate a new dungeon, answered with `DungeonGenerated` or `DungeonGenerationFailed`.
+/// any amount of dungeons can be generated at once, new dungeons are hidden until made visible
+#[derive(Debug, Clone, Event)]
+pub struct GenerateDungeon {
+    /// settings too generate dungeon with, `settings.seed` is replaced by `seed`
+    pub settings: DungeonSettings,
+    /// seed for dungeon rng
+    pub seed: DungeonSeed,
+}
+
+/// sent when a requested dungeon has spawned all its rooms and hallways
+#[derive(Debug, Clone, Copy, Event)]
+pub struct DungeonGenerated {
+    /// dungeon root that finished
+    pub entity: Entity,
+}
+
+/// sent when a requested dungeon could not be generated, its dungeon root is despawned
+#[derive(Debug, Clone, Event)]
+pub struct DungeonGenerationFailed {
+    /// dungeon root that failed
+    pub entity: Entity,
+    /// why generation failed
+    pub reason: String,
+}
+
+/// generation phase of a single dungeon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub enum GenerationPhase {
+    /// layout is being created by a background task
+    #[default]
+    LayingOut,
+    /// rooms and hallway blueprints are spawned, waiting for room levels too spawn
+    SpawningRooms,
+    /// spawning hallway tiles
+    BuildingHallways,
+    /// dungeon is finished and can be played
+    Ready,
+}
+
+/// marks dungeon rooms whose level has been spawned and transformed
+#[derive(Debug, Clone, Copy, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct RoomSpawned;
+
+/// step of dungeon generation currently running
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum GenerationStage {
+    /// task is waiting too be picked up by the task pool
+    #[default]
+    Queued,
+    /// choosing and placing room presets, `attempt` goes up when placement rules are broken
+    PlacingRooms {
+        /// layout attempt, first attempt is 1
+        attempt: u32,
+    },
+    /// connecting room exits into a room graph
+    ConnectingRooms,
+    /// creating tile graph from room collisions
+    BuildingTileGraph,
+    /// pathing hallways through the tile graph
+    RoutingHallways {
+        /// hallways that have a path
+        routed: usize,
+        /// hallways in dungeon
+        total: usize,
+    },
+    /// waiting for room levels too be spawned
+    SpawningRooms {
+        /// rooms that have finished spawning
+        spawned: usize,
+        /// rooms in dungeon
+        total: usize,
+    },
+    /// spawning hallway tiles and walls
+    BuildingHallways,
+    /// dungeon is ready too play
+    Finished,
+}
+
+impl GenerationStage {
+    /// how far along generation is, 0.0 is just started and 1.0 is finished
+    pub fn fraction(self) -> f32 {
+        /// fraction of `done` out of `total`, finished if `total` is 0
+        fn part(done: usize, total: usize) -> f32 {
+            if total == 0 {
+                1.0
+            } else {
+                done as f32 / total as f32
+            }
+        }
+
+        match self {
+            Self::Queued => 0.0,
+            Self::PlacingRooms { .. } => 0.05,
+            Self::ConnectingRooms => 0.15,
+            Self::BuildingTileGraph => 0.25,
+            Self::RoutingHallways { routed, total } => 0.35 + 0.4 * part(routed, total),
+            Self::SpawningRooms { spawned, total } => 0.75 + 0.15 * part(spawned, total),
+            Self::BuildingHallways => 0.9,
+            Self::Finished => 1.0,
+        }
+    }
+}
+
+impl std::fmt::Display for GenerationStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Queued => write!(f, "waiting for generator"),
+            Self::PlacingRooms { attempt: 1 } => write!(f, "placing rooms"),
+            Self::PlacingRooms { attempt } => write!(f, "placing rooms (attempt {attempt})"),
+            Self::ConnectingRooms => write!(f, "connecting rooms"),
+            Self::BuildingTileGraph => write!(f, "mapping room tiles"),
+            Self::RoutingHallways { routed, total } => {
+                write!(f, "routing hallways {routed}/{total}")
+            }
+            Self::SpawningRooms { spawned, total } => {
+                write!(f, "spawning rooms {spawned}/{total}")
+            }
+            Self::BuildingHallways => write!(f, "building hallways"),
+            Self::Finished => write!(f, "finished"),
+        }
+    }
+}
+
+/// sent whenever a dungeons `GenerationStage` changes
+#[derive(Debug, Clone, Copy, Event)]
+pub struct DungeonGenerationProgress {
+    /// dungeon root being generated
+    pub dungeon: Entity,
+    /// step generation is on
+    pub stage: GenerationStage,
+}
+
+/// result of a background layout task, holds the rng so the dungeon can keep using it
+type LayoutTaskResult = Result<(DungeonLayout, StdRng), LayoutError>;
+
+/// background task creating a `DungeonLayout` for the dungeon its attached too
+#[derive(Component)]
+pub struct LayoutTask {
+    /// running layout task
+    task: Task<LayoutTaskResult>,
+    /// stage the task is on, written by task and read by `poll_layout_tasks`
+    progress: Arc<Mutex<GenerationStage>>,
+    /// last stage sent as a `DungeonGenerationProgress` event
+    reported: Option<GenerationStage>,
+}
+
+impl LayoutTask {
+    /// starts creating a layout from `settings` on the async compute task pool
+    fn start(settings: DungeonSettings, room_database: DungeonRoomDatabase, rng: StdRng) -> Self {
+        let progress = Arc::new(Mutex::new(GenerationStage::Queued));
+        let task_progress = Arc::clone(&progress);
+        let mut rng = rng;
+
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            layout::generate_layout_with_progress(
+                &settings,
+                &room_database,
+                &mut rng,
+                &mut |stage| {
+                    if let Ok(mut current) = task_progress.lock() {
+                        *current = stage;
+                    }
+                },
+            )
+            .map(|layout| (layout, rng))
+        });
+
+        Self {
+            task,
+            progress,
+            reported: None,
+        }
+    }
+}
+
+/// spawns a hidden dungeon root for each `GenerateDungeon` request and starts its layout task.
+/// dungeons are placed so they never overlap dungeons that already exist
+pub fn handle_generate_requests(
+    mut cmds: Commands,
+    mut requests: EventReader<GenerateDungeon>,
+    room_database: Option<Res<DungeonRoomDatabase>>,
+    ldtk_project_handles: Res<AspenLevelsetHandles>,
+    existing_dungeons: Query<(&Dungeon, &Transform)>,
+    mut failed_events: EventWriter<DungeonGenerationFailed>,
+) {
+    let mut occupied: Vec<Rect> = existing_dungeons
+        .iter()
+        .map(|(dungeon, transform)| {
+            dungeon_bounds(transform.translation.truncate(), &dungeon.settings)
+        })
+        .collect();
+
+    for GenerateDungeon { settings, seed } in requests.read() {
+        let mut rng = DungeonRng::from_seed(*seed);
+        let origin = free_dungeon_origin(dungeon_origin(&mut rng.0), settings, &occupied);
+        occupied.push(dungeon_bounds(origin, settings));
+        let origin = Transform::from_translation(origin.extend(0.0));
+        info!(
+            "spawning dungeon for floor {} with seed {seed} at {origin:?}",
+            settings.floor
+        );
+
+        let mut settings = settings.clone();
+        settings.seed = *seed;
+
+        let dungeon_id = cmds
+            .spawn((
+                DungeonContainerBundle {
+                    name: format!("The Aspen Halls {}", settings.floor + 1).into(),
+                    dungeon: Dungeon {
+                        settings: settings.clone(),
+                        tile_graph: TileGraph::default(),
+                        room_graph: RoomGraph::default(),
+                        start_position: Vec2::ZERO,
+                    },
+                    ldtk_project: bevy_ecs_ldtk::LdtkProjectHandle {
+                        handle: ldtk_project_handles.default_levels.clone(),
+                    },
+                    spatial: origin,
+                    visual: Visibility::Hidden,
+                    rng: DungeonRng(rng.0.clone()),
+                },
+                GenerationPhase::LayingOut,
+            ))
+            .id();
+
+        let Some(room_database) = &room_database else {
+            failed_events.send(DungeonGenerationFailed {
+                entity: dungeon_id,
+                reason: "room database is not built yet".to_string(),
+            });
+            cmds.entity(dungeon_id).despawn_recursive();
+            continue;
+        };
+
+        cmds.entity(dungeon_id).insert(LayoutTask::start(
+            settings,
+            DungeonRoomDatabase::clone(room_database),
+            rng.0,
+        ));
+    }
+}
+
+/// sends progress of running layout tasks and spawns each dungeon once its layout is finished
+pub fn poll_layout_tasks(
+    mut cmds: Commands,
+    mut dungeon_root: Query<(
+        Entity,
+        &mut LayoutTask,
+        &mut Dungeon,
+        &mut DungeonRng,
+        &mut GenerationPhase,
+        &Transform,
+    )>,
+    mut progress_events: EventWriter<DungeonGenerationProgress>,
+    mut failed_events: EventWriter<DungeonGenerationFailed>,
+) {
+    for (dungeon_id, mut layout_task, mut dungeon, mut rng, mut phase, dungeon_transform) in
+        &mut dungeon_root
+    {
+        let stage = layout_task.progress.lock().map(|f| *f).ok();
+        if let Some(stage) = stage.filter(|f| layout_task.reported != Some(*f)) {
+            layout_task.reported = Some(stage);
+            progress_events.send(DungeonGenerationProgress {
+                dungeon: dungeon_id,
+                stage,
+            });
+        }
+
+        let Some(result) = block_on(future::poll_once(&mut layout_task.task)) else {
+            continue;
+        };
+        cmds.entity(dungeon_id).remove::<LayoutTask>();
+
+        match result {
+            Ok((layout, task_rng)) => {
+                rng.0 = task_rng;
+                progress_events.send(DungeonGenerationProgress {
+                    dungeon: dungeon_id,
+                    stage: GenerationStage::SpawningRooms {
+                        spawned: 0,
+                        total: layout.rooms.len(),
+                    },
+                });
+                spawn_layout(
+                    &mut cmds,
+                    dungeon_id,
+                    &mut dungeon,
+                    dungeon_transform,
+                    layout,
+                );
+                *phase = GenerationPhase::SpawningRooms;
+            }
+            Err(e) => {
+                error!("could not layout dungeon: {e}");
+                failed_events.send(DungeonGenerationFailed {
+                    entity: dungeon_id,
+                    reason: e.to_string(),
+                });
+                cmds.entity(dungeon_id).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// marks rooms as spawned when there level is transformed,
+/// dungeons with every room spawned move on too building hallways
+#[allow(clippy::too_many_arguments)]
+pub fn wait_for_rooms_spawned(
+    mut cmds: Commands,
+    mut spawn_events: EventReader<LevelEvent>,
+    mut dungeons: Query<(Entity, &Dungeon, &mut GenerationPhase, &Children)>,
+    rooms: Query<(Entity, &LevelIid, Has<RoomSpawned>, Option<&Children>), With<RoomBlueprint>>,
+    layers: Query<(), With<LayerMetadata>>,
+    project_assets: Res<Assets<LdtkProject>>,
+    level_assets: Res<AspenLevelsetHandles>,
+    mut progress_events: EventWriter<DungeonGenerationProgress>,
+) {
+    // levels are reused between rooms and dungeons, each event only marks a room
+    // whose own level layers exist so rooms in other dungeons are never marked early
+    let mut newly_spawned: Vec<Entity> = Vec::new();
+    for event in spawn_events.read() {
+        let LevelEvent::Transformed(iid) = event else {
+            continue;
+        };
+        if let Some((room, ..)) = rooms.iter().find(|(room, room_iid, spawned, children)| {
+            *room_iid == iid
+                && !spawned
+                && !newly_spawned.contains(room)
+                && children.is_some_and(|f| f.iter().any(|f| layers.contains(*f)))
+        }) {
+            cmds.entity(room).insert(RoomSpawned);
+            newly_spawned.push(room);
+        }
+    }
+
+    for (dungeon_id, dungeon, mut phase, children) in &mut dungeons {
+        if *phase != GenerationPhase::SpawningRooms {
+            continue;
+        }
+
+        let dungeon_rooms: Vec<_> = children.iter().filter_map(|f| rooms.get(*f).ok()).collect();
+        let total = dungeon_rooms.len();
+        let spawned = dungeon_rooms
+            .iter()
+            .filter(|(room, _, spawned, _)| *spawned || newly_spawned.contains(room))
+            .count();
+        if dungeon_rooms
+            .iter()
+            .any(|(room, ..)| newly_spawned.contains(room))
+        {
+            progress_events.send(DungeonGenerationProgress {
+                dungeon: dungeon_id,
+                stage: GenerationStage::SpawningRooms { spawned, total },
+            });
+        }
+
+        if total == 0 || spawned < total {
+            trace!("not all rooms are spawned yet");
+            continue;
+        }
+
+        info!("all dungeon rooms spawned");
+        create_hallway_layer(
+            &mut cmds,
+            dungeon_id,
+            dungeon,
+            &project_assets,
+            &level_assets,
+        );
+        *phase = GenerationPhase::BuildingHallways;
+        progress_events.send(DungeonGenerationProgress {
+            dungeon: dungeon_id,
+            stage: GenerationStage::BuildingHallways,
+        });
+    }
+}
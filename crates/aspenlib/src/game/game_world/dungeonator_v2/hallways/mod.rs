@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
 use bevy_ecs_ldtk::prelude::LdtkProject;
 use bevy_ecs_tilemap::{
     map::{TilemapTexture, TilemapTileSize, TilemapType},
@@ -17,9 +17,19 @@ use crate::{
 
 /// hallway creation functions
 pub mod hallway_builder;
+/// hallway path finding
+pub mod pathfinding;
 /// wall creation functions
 pub mod walls;
 
+/// thinnest hallway that can be built, in tiles
+pub const MIN_HALLWAY_WIDTH: u32 = 1;
+/// widest hallway that can be built, in tiles
+pub const MAX_HALLWAY_WIDTH: u32 = 5;
+/// ldtk tileset hallways are built from when a floors tileset is missing,
+/// every hallway tileset must use the same layout as this one
+pub const DEFAULT_HALLWAY_TILESET: &str = "Hallway_Tiles";
+
 /// amount of this type that shares parents
 #[derive(Debug, PartialEq, Eq, PartialOrd, Clone, Reflect)]
 pub struct SiblingCount(pub u32);
@@ -33,27 +43,50 @@ pub struct HallWayBlueprint {
     pub end_pos: IVec2,
     /// how long is pathway
     pub distance: f32,
+    /// how many tiles wide this hallway is built
+    pub width: u32,
     /// rooms connected too hallway
     pub connected_rooms: (RoomID, RoomID),
+    /// exit covered by a breakable wall, set when this hallway leads too a secret room
+    pub hidden_exit: Option<IVec2>,
     /// list of nodes in tilegraph that are hallway tiles
     #[reflect(ignore)]
     pub node_path: VecDeque<NodeIndex>,
+    /// tile coordinates of this hallways floor, filled when hallway is built
+    #[reflect(ignore)]
+    pub floor_tiles: HashSet<UVec2>,
     /// hallway finished building
     pub built: bool,
 }
 
+/// tile offsets covered by a hallway of `width` around each of its path tiles,
+/// even widths lean too the north east
+pub fn hallway_brush(width: u32) -> impl Iterator<Item = IVec2> {
+    let width = width.clamp(MIN_HALLWAY_WIDTH, MAX_HALLWAY_WIDTH) as i32;
+    let min = -(width - 1) / 2;
+    let max = min + width - 1;
+
+    (min..=max).flat_map(move |x| (min..=max).map(move |y| IVec2 { x, y }))
+}
+
+/// `tile` moved by `offset`, none if that would be below tile 0
+pub fn offset_tile(tile: UVec2, offset: IVec2) -> Option<UVec2> {
+    let moved = tile.as_ivec2() + offset;
+    moved.cmpge(IVec2::ZERO).all().then(|| moved.as_uvec2())
+}
+
 /// marker component for hallway tile storage
 #[derive(Debug, Component)]
 pub struct HallwayLayer;
 
-/// creates hallway storage entity for further modification
+/// creates hallway storage entity under `dungeon_entity` for further modification
 pub fn create_hallway_layer(
-    mut cmds: Commands,
-    dungeon: Query<(Entity, &Dungeon, &GlobalTransform)>,
-    project_assets: Res<Assets<LdtkProject>>,
-    level_assets: Res<AspenLevelsetHandles>,
+    cmds: &mut Commands,
+    dungeon_entity: Entity,
+    dungeon_info: &Dungeon,
+    project_assets: &Assets<LdtkProject>,
+    level_assets: &AspenLevelsetHandles,
 ) {
-    let (dungeon_entity, dungeon_info, dungeon_global_transform) = dungeon.single();
     let Dungeon {
         settings,
         tile_graph,
@@ -70,10 +103,29 @@ pub fn create_hallway_layer(
     let project = project_assets
         .get(project_handle.id())
         .expect("asset should exist");
+    // floors pick there hallway tileset, default tiles are used if it is missing
+    let tileset_uid = |identifier: &str| {
+        project
+            .json_data()
+            .defs
+            .tilesets
+            .iter()
+            .find(|f| f.identifier == identifier)
+            .map(|f| f.uid)
+    };
+    let hallway_tileset_uid = tileset_uid(&settings.hallway_tileset)
+        .or_else(|| {
+            warn!(
+                "no hallway tileset named {:?}, using default hallway tiles",
+                settings.hallway_tileset
+            );
+            tileset_uid(DEFAULT_HALLWAY_TILESET)
+        })
+        .expect("default hallway tileset should exist");
     let hallway_tileset = project
         .tileset_map()
-        .get(&89) // TODO: magic numbers are bad
-        .expect("tileset uid should not change");
+        .get(&hallway_tileset_uid)
+        .expect("tileset uid was found in project");
     let tile_size = TilemapTileSize {
         x: TILE_SIZE,
         y: TILE_SIZE,
@@ -81,9 +133,8 @@ pub fn create_hallway_layer(
     let grid_size = tile_size.into();
     let map_type = TilemapType::default();
 
-    let min_tile_pos = tile_graph.get_tiles_translation_world(settings, UVec2::ZERO);
-    let dungeon_global_transform = dungeon_global_transform.translation().truncate();
-    let hallway_tiles_origin = min_tile_pos - dungeon_global_transform;
+    // tile graph is relative too dungeon so this is already local
+    let hallway_tiles_origin = tile_graph.get_tiles_translation_world(settings, UVec2::ZERO);
 
     cmds.entity(hallway_container).insert(TilemapBundle {
         grid_size,
@@ -0,0 +1,167 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use bevy::{math::FloatOrd, prelude::*, utils::HashMap};
+use petgraph::prelude::NodeIndex;
+
+use crate::game::game_world::dungeonator_v2::{
+    components::{DungeonSettings, HallwayPathCosts},
+    hallways::{offset_tile, HallWayBlueprint},
+    tile_graph::TileGraph,
+};
+
+/// node in the tile graph plus the direction it was entered from,
+/// direction is needed so turns can be priced
+type SearchState = (NodeIndex, Option<IVec2>);
+
+/// finds path start and end nodes and returns a `Vec<NodeIndex>` between the 2.
+/// hallways too secret rooms never touch existing hallways so the breakable wall can not be walked around
+///
+/// hallway positions and `tile_graph` are both relative too the dungeon origin
+pub fn create_hallway_path(
+    settings: &DungeonSettings,
+    tile_graph: &TileGraph,
+    hallway: &HallWayBlueprint,
+) -> Option<VecDeque<NodeIndex>> {
+    trace!("getting hallway side nodes");
+    let start_node_position = hallway.start_pos + IVec2::splat(16);
+    let end_node_position = hallway.end_pos + IVec2::splat(16);
+
+    let Some(start) = tile_graph.get_node_at_translation(settings, start_node_position) else {
+        error!("could not get start node for {:?}", start_node_position);
+        return None;
+    };
+    let Some(end) = tile_graph.get_node_at_translation(settings, end_node_position) else {
+        error!("Could not get end node for {:?}", end_node_position);
+        return None;
+    };
+
+    trace!("calculating path for {:?}", hallway.connected_rooms);
+    // room for this hallways own floor and a wall between it and other hallways
+    let clearance = hallway.hidden_exit.map(|_| hallway.width / 2 + 1);
+    let path = astar_path(tile_graph, start, end, &settings.path_costs, clearance);
+
+    if path.is_none() {
+        let path_start_pos = tile_graph[start].tile;
+        let path_end_pos = tile_graph[end].tile;
+        error!(
+            "no path between {:?} and {:?}",
+            tile_graph.get_tiles_translation_world(settings, path_start_pos),
+            tile_graph.get_tiles_translation_world(settings, path_end_pos)
+        );
+    }
+
+    path
+}
+
+/// A* search between `start` and `end` using manhattan distance,
+/// `costs` shapes the path too prefer straight corridors away from walls and other hallways.
+/// with a `clearance` tiles within that many tiles of a hallway can not be used
+pub fn astar_path(
+    tile_graph: &TileGraph,
+    start: NodeIndex,
+    end: NodeIndex,
+    costs: &HallwayPathCosts,
+    clearance: Option<u32>,
+) -> Option<VecDeque<NodeIndex>> {
+    let goal = tile_graph[end].tile.as_ivec2();
+    let heuristic = |node: NodeIndex| {
+        let offset = (tile_graph[node].tile.as_ivec2() - goal).abs();
+        (offset.x + offset.y) as f32 * costs.step
+    };
+
+    let start_state: SearchState = (start, None);
+    let mut states: Vec<SearchState> = vec![start_state];
+    let mut state_ids: HashMap<SearchState, usize> = HashMap::new();
+    let mut best_cost: HashMap<usize, f32> = HashMap::new();
+    let mut came_from: HashMap<usize, usize> = HashMap::new();
+    let mut open = BinaryHeap::new();
+
+    state_ids.insert(start_state, 0);
+    best_cost.insert(0, 0.0);
+    open.push(Reverse((FloatOrd(heuristic(start)), 0)));
+
+    while let Some(Reverse((FloatOrd(estimate), current_id))) = open.pop() {
+        let (current, entered_with) = states[current_id];
+        if current == end {
+            return Some(reconstruct_path(&states, &came_from, current_id));
+        }
+
+        let current_cost = best_cost[&current_id];
+        // a cheaper way too this state was found after this entry was queued
+        if estimate > current_cost + heuristic(current) {
+            continue;
+        }
+        let current_pos = tile_graph[current].tile.as_ivec2();
+
+        for next in tile_graph.neighbors(current) {
+            let next_node = &tile_graph[next];
+            if clearance.is_some_and(|radius| near_hallway(tile_graph, next_node.tile, radius)) {
+                continue;
+            }
+            let direction = next_node.tile.as_ivec2() - current_pos;
+
+            let mut step_cost = costs.step;
+            if entered_with.is_some_and(|dir| dir != direction) {
+                step_cost += costs.turn;
+            }
+            // walkable tiles with missing neighbors are next too rooms
+            if tile_graph.neighbors(next).count() < 4 {
+                step_cost += costs.wall_adjacent;
+            }
+            if next_node.data.is_hallway() {
+                step_cost += costs.hallway_crossing;
+            }
+
+            let next_state: SearchState = (next, Some(direction));
+            let next_id = *state_ids.entry(next_state).or_insert_with(|| {
+                states.push(next_state);
+                states.len() - 1
+            });
+
+            let next_cost = current_cost + step_cost;
+            if best_cost
+                .get(&next_id)
+                .is_some_and(|known| *known <= next_cost)
+            {
+                continue;
+            }
+
+            best_cost.insert(next_id, next_cost);
+            came_from.insert(next_id, current_id);
+            open.push(Reverse((FloatOrd(next_cost + heuristic(next)), next_id)));
+        }
+    }
+
+    None
+}
+
+/// is any hallway tile within `radius` tiles of `tile`
+fn near_hallway(tile_graph: &TileGraph, tile: UVec2, radius: u32) -> bool {
+    let radius = radius as i32;
+    (-radius..=radius)
+        .flat_map(|x| (-radius..=radius).map(move |y| IVec2 { x, y }))
+        .filter_map(|offset| offset_tile(tile, offset))
+        .filter_map(|f| tile_graph.get_node_at_coord(f))
+        .any(|f| tile_graph[f].data.is_hallway())
+}
+
+/// walks `came_from` back too the start state
+fn reconstruct_path(
+    states: &[SearchState],
+    came_from: &HashMap<usize, usize>,
+    end_id: usize,
+) -> VecDeque<NodeIndex> {
+    let mut path = VecDeque::new();
+    let mut current_id = end_id;
+
+    path.push_front(states[current_id].0);
+    while let Some(previous_id) = came_from.get(&current_id) {
+        current_id = *previous_id;
+        path.push_front(states[current_id].0);
+    }
+
+    path
+}
@@ -0,0 +1,506 @@
+use std::{cmp::Reverse, collections::VecDeque};
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use petgraph::{data::FromElements, prelude::EdgeRef, Graph};
+use rand::{prelude::Rng, seq::SliceRandom};
+
+use crate::{
+    consts::TILE_SIZE,
+    game::game_world::{
+        dungeonator_v2::{
+            components::{
+                DungeonRoomDatabase, DungeonSettings, RoomBlueprint, RoomID, RoomPreset, RoomType,
+            },
+            generation::GenerationStage,
+            hallways::{
+                hallway_builder::mark_path_as_hallway_tiles, pathfinding::create_hallway_path,
+                HallWayBlueprint,
+            },
+            placement::RoomPlacer,
+            room_graph::{rules::PlacementRule, RoomGraph},
+            tile_graph::{r#impl::build_tilegraph, TileGraph},
+            utils,
+        },
+        random_point_inside,
+    },
+};
+
+/// complete dungeon layout, created without spawning anything.
+/// all positions are relative too the dungeon origin
+#[derive(Debug, Clone)]
+pub struct DungeonLayout {
+    /// settings this layout was made with, `size` is updated too the final tile map size
+    pub settings: DungeonSettings,
+    /// placed rooms, start room is always first
+    pub rooms: Vec<RoomBlueprint>,
+    /// rooms, exits and hallway connections
+    pub room_graph: RoomGraph,
+    /// walkability of every tile in the dungeon, hallway paths are marked
+    pub tile_graph: TileGraph,
+    /// hallways with their paths through `tile_graph`
+    pub hallways: Vec<HallWayBlueprint>,
+    /// where the player is placed when the dungeon is entered
+    pub start_position: Vec2,
+}
+
+/// how many times a layout is retried when its rooms or hallways do not fit,
+/// layouts breaking a `PlacementRule` are retried until one follows the rules
+const MAX_LAYOUT_ATTEMPTS: u32 = 10;
+
+/// reasons a dungeon layout could not be created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// room database has no `DungeonStart` rooms
+    NoStartRoom,
+    /// room database has no `DungeonEnd` rooms
+    NoEndRoom,
+    /// dungeon has too few normal or mini boss rooms for a placement rule too ever be followed
+    TooFewRooms {
+        /// rule that can not be followed
+        rule: PlacementRule,
+        /// rooms the rule needs
+        needed: usize,
+        /// rooms in the dungeon on the last attempt
+        rooms: usize,
+    },
+    /// rooms and there spacing need more tiles than the dungeon has inside its border
+    DistributionTooLarge {
+        /// tiles needed by rooms, including `tiles_between_rooms`
+        needed: i64,
+        /// tiles inside dungeon border
+        available: i64,
+    },
+    /// every layout attempt had a hallway with no path between its rooms
+    HallwayBlocked {
+        /// names of the rooms the hallway connects on the last attempt
+        rooms: (String, String),
+    },
+    /// every layout attempt had a secret room with no free exit too attach too,
+    /// or whose hallway could not avoid other hallways
+    SecretHallwayBlocked {
+        /// name of the secret room on the last attempt
+        room: String,
+    },
+    /// every layout attempt ran out of space while placing rooms
+    RoomsDoNotFit {
+        /// name of the room that could not be placed on the last attempt
+        room: String,
+        /// rooms placed before running out of space
+        placed: usize,
+        /// rooms in the dungeon
+        total: usize,
+    },
+}
+
+impl std::fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoStartRoom => write!(f, "room database has no dungeon start rooms"),
+            Self::NoEndRoom => write!(f, "room database has no dungeon end rooms"),
+            Self::TooFewRooms {
+                rule,
+                needed,
+                rooms,
+            } => write!(
+                f,
+                "placement rule '{rule}' needs {needed} normal or mini boss rooms but dungeon only has {rooms}, add rooms or change the rule"
+            ),
+            Self::DistributionTooLarge { needed, available } => write!(
+                f,
+                "room distribution needs {needed} tiles but dungeon only has {available} tiles inside its border, increase size or remove rooms"
+            ),
+            Self::HallwayBlocked { rooms } => write!(
+                f,
+                "no path for hallway between {} and {} after {MAX_LAYOUT_ATTEMPTS} attempts",
+                rooms.0, rooms.1
+            ),
+            Self::SecretHallwayBlocked { room } => write!(
+                f,
+                "no hallway too secret room {room} could be made after {MAX_LAYOUT_ATTEMPTS} attempts"
+            ),
+            Self::RoomsDoNotFit {
+                room,
+                placed,
+                total,
+            } => write!(
+                f,
+                "no space for room {room} after placing {placed} of {total} rooms in {MAX_LAYOUT_ATTEMPTS} attempts, increase size or remove rooms"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// creates a full `DungeonLayout` from `settings` and `room_database`
+///
+/// does not touch the world so it can be used for tests, benchmarks and background tasks.
+/// same `rng` state, settings and database always creates the same layout
+///
+/// # Errors
+/// returns `LayoutError` if `room_database` is missing rooms needed for a dungeon
+/// or has too few rooms for `settings.placement_rules`, or no layout could fit its rooms and hallways
+pub fn generate_layout(
+    settings: &DungeonSettings,
+    room_database: &DungeonRoomDatabase,
+    rng: &mut impl Rng,
+) -> Result<DungeonLayout, LayoutError> {
+    generate_layout_with_progress(settings, room_database, rng, &mut |_| {})
+}
+
+/// same as `generate_layout` but calls `progress` each time generation moves too a new `GenerationStage`
+///
+/// # Errors
+/// returns `LayoutError` if `room_database` is missing rooms needed for a dungeon
+/// or has too few rooms for `settings.placement_rules`, or no layout could fit its rooms and hallways
+pub fn generate_layout_with_progress(
+    settings: &DungeonSettings,
+    room_database: &DungeonRoomDatabase,
+    rng: &mut impl Rng,
+    progress: &mut impl FnMut(GenerationStage),
+) -> Result<DungeonLayout, LayoutError> {
+    let mut attempt = 0;
+    let (layout_settings, rooms, room_graph, tile_graph, hallways) = loop {
+        attempt += 1;
+        progress(GenerationStage::PlacingRooms { attempt });
+        let (presets, rooms, mut room_graph) =
+            match create_room_graph(settings, room_database, rng, progress) {
+                Ok(created) => created,
+                Err(
+                    error @ (LayoutError::RoomsDoNotFit { .. }
+                    | LayoutError::SecretHallwayBlocked { .. }),
+                ) if attempt < MAX_LAYOUT_ATTEMPTS => {
+                    warn!("layout attempt {attempt} failed: {error}, retrying");
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+        info!("checking placement rules");
+        let between_rooms = rooms
+            .iter()
+            .filter(|f| matches!(f.descriptor.rtype, RoomType::Normal | RoomType::MiniBoss))
+            .count();
+        if let Some(rule) = settings
+            .placement_rules
+            .iter()
+            .find(|f| f.rooms_needed() > between_rooms)
+        {
+            let error = LayoutError::TooFewRooms {
+                rule: *rule,
+                needed: rule.rooms_needed(),
+                rooms: between_rooms,
+            };
+            // mini bosses are chosen from the special rooms so another attempt can have more
+            if attempt < MAX_LAYOUT_ATTEMPTS {
+                warn!("layout attempt {attempt} failed: {error}, retrying");
+                continue;
+            }
+            return Err(error);
+        }
+        // rules are possible with these rooms, so keep trying new layouts until one follows them
+        if let Err(violation) = room_graph.enforce_rules(&settings.placement_rules) {
+            warn!("layout attempt {attempt} broke rule {violation}, retrying");
+            continue;
+        }
+
+        info!("creating tile graph from room collisions");
+        progress(GenerationStage::BuildingTileGraph);
+        let mut layout_settings = settings.clone();
+        let placed_rooms: Vec<(&RoomPreset, &RoomBlueprint)> =
+            presets.iter().copied().zip(rooms.iter()).collect();
+        let mut tile_graph = build_tilegraph(&mut layout_settings, &placed_rooms);
+
+        info!("creating hallway paths");
+        match create_hallways(&layout_settings, &room_graph, &mut tile_graph, progress) {
+            Ok(hallways) => break (layout_settings, rooms, room_graph, tile_graph, hallways),
+            Err(error) if attempt < MAX_LAYOUT_ATTEMPTS => {
+                warn!("layout attempt {attempt} failed: {error}, retrying");
+            }
+            Err(error) => return Err(error),
+        }
+    };
+
+    let start_position = start_position(rng);
+
+    Ok(DungeonLayout {
+        settings: layout_settings,
+        rooms,
+        room_graph,
+        tile_graph,
+        hallways,
+        start_position,
+    })
+}
+
+/// chooses and places room presets then connects them into a `RoomGraph`
+fn create_room_graph<'a>(
+    settings: &'a DungeonSettings,
+    room_database: &'a DungeonRoomDatabase,
+    rng: &mut impl Rng,
+    progress: &mut impl FnMut(GenerationStage),
+) -> Result<(Vec<&'a RoomPreset>, Vec<RoomBlueprint>, RoomGraph), LayoutError> {
+    info!("choosing room presets");
+    let presets = choose_presets(settings, room_database, rng)?;
+
+    info!("creating dungeon room blueprints");
+    let rooms = position_presets(&presets, settings, rng)?;
+
+    info!("creating room graph from blueprints");
+    progress(GenerationStage::ConnectingRooms);
+    let (secret_rooms, connected_rooms): (Vec<RoomBlueprint>, Vec<RoomBlueprint>) = rooms
+        .iter()
+        .cloned()
+        .partition(|room| room.descriptor.rtype == RoomType::Secret);
+    let mut room_graph = RoomGraph::new(&connected_rooms);
+
+    info!("connecting graph");
+    room_graph.connect_graph_randomly(rng);
+
+    info!("computing minimum spanning tree of graph");
+    let connected_graph = room_graph.graph.clone();
+    room_graph.graph = Graph::from_elements(petgraph::algo::min_spanning_tree(&room_graph.graph));
+
+    info!("verifying graph connectivity");
+    room_graph.verify_graph_connections();
+
+    info!("adding hallway loops");
+    room_graph.add_loop_edges(&connected_graph, settings.hallway_loop_chance);
+
+    info!("attaching secret rooms");
+    for secret_room in &secret_rooms {
+        // a secret room without its hallway could never be entered
+        if !room_graph.attach_secret_room(secret_room) {
+            return Err(LayoutError::SecretHallwayBlocked {
+                room: secret_room.name.clone(),
+            });
+        }
+    }
+
+    Ok((presets, rooms, room_graph))
+}
+
+/// chooses every preset used in dungeon, start room first and end room last
+fn choose_presets<'a>(
+    settings: &'a DungeonSettings,
+    room_database: &'a DungeonRoomDatabase,
+    rng: &mut impl Rng,
+) -> Result<Vec<&'a RoomPreset>, LayoutError> {
+    let mut presets = utils::choose_filler_presets(settings, room_database, rng);
+    if presets.is_empty() {
+        warn!("no filler presets could be chosen from room database");
+    }
+
+    // secret rooms are placed like every other room but connected after the graph is made
+    if room_database.secret_rooms.is_empty() {
+        if settings.distribution.secret > 0 {
+            warn!(
+                "distribution wants {} secret rooms but room database has no secret rooms",
+                settings.distribution.secret
+            );
+        }
+    } else {
+        for _ in 0..settings.distribution.secret {
+            if let Some(secret) =
+                utils::get_floor_preset(&room_database.secret_rooms, settings, rng)
+            {
+                presets.push_back(secret);
+            }
+        }
+    }
+
+    // add start and end presets
+    presets.push_back(
+        utils::get_floor_preset(&room_database.end_rooms, settings, rng)
+            .ok_or(LayoutError::NoEndRoom)?,
+    );
+    presets.push_front(
+        utils::get_floor_preset(&room_database.start_rooms, settings, rng)
+            .ok_or(LayoutError::NoStartRoom)?,
+    );
+
+    Ok(presets.into())
+}
+
+/// turns each preset randomly and places it inside the dungeon border, creating its `RoomBlueprint`.
+/// start room is placed at the dungeon origin and larger rooms are placed first so they still fit
+fn position_presets(
+    presets: &[&RoomPreset],
+    settings: &DungeonSettings,
+    rng: &mut impl Rng,
+) -> Result<Vec<RoomBlueprint>, LayoutError> {
+    let mut placer = RoomPlacer::new(settings);
+
+    let orientations: Vec<_> = presets
+        .iter()
+        .map(|preset| preset.orientations.choose(rng).copied().unwrap_or_default())
+        .collect();
+    let room_sizes: Vec<IVec2> = presets
+        .iter()
+        .zip(&orientations)
+        .map(|(preset, orientation)| orientation.oriented_size(preset.size) / TILE_SIZE as i32)
+        .collect();
+
+    let (needed, available) = placer.required_space(&room_sizes);
+    if needed > available {
+        return Err(LayoutError::DistributionTooLarge { needed, available });
+    }
+
+    let mut placement_order: Vec<usize> = (0..presets.len()).collect();
+    placement_order.sort_by_key(|i| {
+        let is_start = presets[*i].descriptor.rtype == RoomType::DungeonStart;
+        (!is_start, Reverse(room_sizes[*i].x * room_sizes[*i].y))
+    });
+
+    let mut room_spaces = vec![IRect::default(); presets.len()];
+    for (placed, i) in placement_order.into_iter().enumerate() {
+        let size = room_sizes[i];
+        let room_space = if presets[i].descriptor.rtype == RoomType::DungeonStart {
+            Some(IRect::from_corners(-size / 2, -size / 2 + size)).filter(|f| placer.fits(*f))
+        } else {
+            placer.find_position(size, rng)
+        }
+        .ok_or_else(|| LayoutError::RoomsDoNotFit {
+            room: presets[i].name.clone(),
+            placed,
+            total: presets.len(),
+        })?;
+
+        placer.place(room_space);
+        room_spaces[i] = room_space;
+    }
+
+    Ok(presets
+        .iter()
+        .zip(orientations)
+        .zip(room_spaces)
+        .enumerate()
+        .map(|(i, ((preset, orientation), room_space))| {
+            RoomBlueprint::from_preset(
+                preset,
+                room_space.min * TILE_SIZE as i32,
+                orientation,
+                i as u32,
+            )
+        })
+        .collect())
+}
+
+/// creates hallway blueprint for each exit too exit edge in `room_graph`
+/// and paths it through `tile_graph`.
+/// hallways too secret rooms are routed last so no other hallway can cross them
+///
+/// # Errors
+/// returns `LayoutError::HallwayBlocked` if a hallway has no path between its rooms,
+/// or `LayoutError::SecretHallwayBlocked` if a secret room hallway can not avoid other hallways
+fn create_hallways(
+    settings: &DungeonSettings,
+    room_graph: &RoomGraph,
+    tile_graph: &mut TileGraph,
+    progress: &mut impl FnMut(GenerationStage),
+) -> Result<Vec<HallWayBlueprint>, LayoutError> {
+    let mut hallways = Vec::new();
+    let rooms: HashMap<RoomID, &RoomBlueprint> = room_graph
+        .get_rooms()
+        .into_iter()
+        .map(|room| (room.id, room))
+        .collect();
+    let room_types: HashMap<RoomID, &RoomType> = rooms
+        .iter()
+        .map(|(id, room)| (*id, &room.descriptor.rtype))
+        .collect();
+
+    let mut exit_edges: Vec<_> = room_graph
+        .edge_references()
+        .filter(|edge| {
+            let source = room_graph.graph.node_weight(edge.source()).expect("msg");
+            let target = room_graph.graph.node_weight(edge.target()).expect("msg");
+
+            let is_hallway = target.is_exit() && source.is_exit() && source != target;
+            if !is_hallway {
+                trace!("skipping room too exit edge");
+            }
+            is_hallway
+        })
+        .collect();
+    exit_edges.sort_by_key(|edge| {
+        [edge.source(), edge.target()].iter().any(|f| {
+            let node = room_graph.graph.node_weight(*f).expect("msg");
+            *room_types[node.get_node_id()] == RoomType::Secret
+        })
+    });
+    let total = exit_edges.len();
+
+    for (routed, edge) in exit_edges.into_iter().enumerate() {
+        progress(GenerationStage::RoutingHallways { routed, total });
+        let source = room_graph.graph.node_weight(edge.source()).expect("msg");
+        let target = room_graph.graph.node_weight(edge.target()).expect("msg");
+        // exits always belong too a room in the graph
+        let width = settings.hallway_widths.width_between(
+            room_types[source.get_node_id()],
+            room_types[target.get_node_id()],
+        );
+
+        // secret rooms are hidden behind a breakable wall on the other rooms exit
+        let (hidden_exit, secret_room) = if *room_types[target.get_node_id()] == RoomType::Secret {
+            (Some(source.get_nodes_offset()), Some(target.get_node_id()))
+        } else if *room_types[source.get_node_id()] == RoomType::Secret {
+            (Some(target.get_nodes_offset()), Some(source.get_node_id()))
+        } else {
+            (None, None)
+        };
+
+        let mut hallway = HallWayBlueprint {
+            start_pos: source.get_nodes_offset(),
+            end_pos: target.get_nodes_offset(),
+            distance: edge.weight().length,
+            width,
+            node_path: VecDeque::new(),
+            floor_tiles: HashSet::new(),
+            connected_rooms: (*source.get_node_id(), *target.get_node_id()),
+            hidden_exit,
+            built: false,
+        };
+
+        info!("generating path for hallway {:?}", hallway.connected_rooms);
+        match create_hallway_path(settings, tile_graph, &hallway) {
+            Some(path) if path.len() >= 2 => {
+                mark_path_as_hallway_tiles(&path, width, tile_graph);
+                hallway.node_path = path;
+            }
+            _ => {
+                if let Some(secret_room) = secret_room {
+                    return Err(LayoutError::SecretHallwayBlocked {
+                        room: rooms[secret_room].name.clone(),
+                    });
+                }
+                // rooms past this hallway could never be reached
+                return Err(LayoutError::HallwayBlocked {
+                    rooms: (
+                        rooms[source.get_node_id()].name.clone(),
+                        rooms[target.get_node_id()].name.clone(),
+                    ),
+                });
+            }
+        }
+
+        hallways.push(hallway);
+    }
+    progress(GenerationStage::RoutingHallways {
+        routed: total,
+        total,
+    });
+
+    Ok(hallways)
+}
+
+/// random position near the center of the start room
+fn start_position(rng: &mut impl Rng) -> Vec2 {
+    let start_size = Vec2 { x: 50.0, y: 50.0 };
+    let start_loc_rect = Rect::from_center_half_size(Vec2::ZERO, start_size);
+
+    random_point_inside(&start_loc_rect, 1.0, rng).unwrap_or(Vec2::ZERO)
+}
@@ -1,7 +1,4 @@
-use bevy_ecs_tilemap::prelude::TilemapSize;
-use petgraph::{data::FromElements, prelude::EdgeRef, Graph};
 use rand::prelude::Rng;
-use std::collections::VecDeque;
 
 use bevy::{prelude::*, reflect::Reflect};
 use bevy_ecs_ldtk::{assets::LdtkExternalLevel, prelude::LdtkProject};
@@ -11,62 +8,67 @@ use crate::{
     game::{
         characters::player::PlayerSelectedHero,
         game_world::{
-            components::{ActorTeleportEvent, RoomExit, TpTriggerEffect},
+            components::{ActorTeleportEvent, RoomExit, SpawnerWave, TpTriggerEffect},
             dungeonator_v2::{
                 components::{
-                    Dungeon, DungeonContainerBundle, DungeonHallWayBundle, DungeonRoomBundle,
-                    DungeonRoomDatabase, DungeonSettings, RoomBlueprint, RoomDistribution,
-                    RoomPreset, RoomType,
+                    Dungeon, DungeonHallWayBundle, DungeonRoomBundle, DungeonRoomDatabase,
+                    DungeonSeed, DungeonSettings, HallwayPathCosts, HallwayWidths, RoomBiome,
+                    RoomBlueprint, RoomDistribution, RoomOrientation, RoomPreset,
                 },
-                hallways::{create_hallway_layer, HallWayBlueprint, HallwayLayer},
-                room_graph::RoomGraph,
-                tile_graph::TileGraph,
+                floors::{ActiveDungeon, DungeonRun},
+                generation::{
+                    DungeonGenerated, DungeonGenerationFailed, DungeonGenerationProgress,
+                    GenerateDungeon, GenerationPhase, GenerationStage, RoomSpawned,
+                },
+                hallways::HallWayBlueprint,
+                layout::DungeonLayout,
+                room_database::{RoomDatabaseReport, RoomValidationIssue},
             },
-            random_point_inside,
         },
     },
-    loading::assets::AspenLevelsetHandles,
+    loading::{assets::AspenLevelsetHandles, registry::ActorRegistry},
     register_types,
 };
 
-// TODO: make this an async module? handle competion then change gamestate.
-// currently we do the reverse
-// https://github.com/fortress-build/whirlwind
-
 /// Dungeon Generator components
 pub mod components;
+/// dungeon layout export too json and png
+pub mod export;
+/// floor tables and multi floor runs
+pub mod floors;
+/// background layout task and generation progress
+pub mod generation;
 /// hallway creation system
 pub mod hallways;
+/// headless dungeon layout creation
+pub mod layout;
+/// room placement inside the dungeon border
+pub mod placement;
 /// room selection and creation
 pub mod room_database;
 /// per dungeon graph of rooms and connections
 pub mod room_graph;
+/// property tests for layout generation
+#[cfg(test)]
+mod tests;
 /// global tile graph map thing
 pub mod tile_graph;
 /// dungeon generation utilitys
 pub mod utils;
 
 /// are we in dungeon yet?
+/// tracks the dungeon the player is playing, each dungeon tracks its own `GenerationPhase`
 #[derive(Debug, Clone, Eq, PartialEq, Hash, States, Default, Reflect)]
 pub enum GeneratorState {
     /// no dungeon is spawned
     #[default]
     NoDungeon,
-    /// select presets
+    /// waiting for the current floors dungeon too finish generating
     LayoutDungeon,
-    /// spawns rooms in world
-    CompleteHallways,
-    /// build hallway tiles for each blueprint
-    FinalizeHallways,
     /// finished making dunegon
     FinishedDungeonGen,
 }
 
-// TODO: implemenet as external reusable plugin
-//fire events for dungeons instead of using states as we do.
-// maybe wrap `GeneratorState` inside an event struct (<--, better imo, can more data) or make it an event
-// this should make enabling multiple dungeons alot easier
-
 /// generates dungeons from ldtk level files
 pub struct DungeonGeneratorPlugin;
 
@@ -81,12 +83,29 @@ impl Plugin for DungeonGeneratorPlugin {
                 RoomBlueprint,
                 HallWayBlueprint,
                 RoomDistribution,
+                RoomOrientation,
+                RoomBiome,
+                DungeonSeed,
                 DungeonSettings,
-                DungeonRoomDatabase
+                DungeonRun,
+                HallwayPathCosts,
+                HallwayWidths,
+                DungeonRoomDatabase,
+                RoomDatabaseReport,
+                RoomValidationIssue,
+                GenerationStage,
+                GenerationPhase,
+                RoomSpawned,
+                ActiveDungeon
             ]
         );
 
-        app.init_state::<GeneratorState>();
+        app.init_state::<GeneratorState>()
+            .init_resource::<DungeonRun>()
+            .add_event::<GenerateDungeon>()
+            .add_event::<DungeonGenerated>()
+            .add_event::<DungeonGenerationFailed>()
+            .add_event::<DungeonGenerationProgress>();
 
         // create a new room database anytime we get new room assets
         app.add_systems(
@@ -99,237 +118,130 @@ impl Plugin for DungeonGeneratorPlugin {
             ),
         );
 
-        // step 1 create graph
+        // every requested dungeon moves through its `GenerationPhase` on its own
         app.add_systems(
-            OnEnter(GeneratorState::LayoutDungeon),
+            Update,
             (
-                spawn_new_dungeon,
-                apply_deferred,
-                layout_dungeon,
-                apply_deferred,
-            )
-                .before(TransformSystem::TransformPropagate)
-                .chain(),
+                (
+                    generation::handle_generate_requests
+                        .run_if(resource_exists::<AspenLevelsetHandles>),
+                    generation::poll_layout_tasks,
+                )
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+                generation::wait_for_rooms_spawned.after(TransformSystem::TransformPropagate),
+                hallways::hallway_builder::build_hallways.after(generation::wait_for_rooms_spawned),
+            ),
         );
 
-        // create tilegraph by premtively filling in spots based on roomgraph data, plus the requested border for rooms instead of below systems
+        // request the players floor, then enter it when its generated
         app.add_systems(
-            Update,
-            (tile_graph::create_tile_graph, apply_deferred)
-                .chain()
-                .after(TransformSystem::TransformPropagate)
-                .run_if(in_state(GeneratorState::CompleteHallways)),
+            OnEnter(GeneratorState::LayoutDungeon),
+            floors::request_floor_dungeon,
         );
-
         app.add_systems(
-            OnEnter(GeneratorState::FinalizeHallways),
-            (create_hallway_layer, apply_deferred).chain(),
+            Update,
+            floors::enter_floor_dungeon.run_if(in_state(GeneratorState::LayoutDungeon)),
         );
 
         app.add_systems(
             Update,
-            hallways::hallway_builder::build_hallways.run_if(
-                in_state(GeneratorState::FinalizeHallways)
-                    .and(any_with_component::<HallwayLayer>),
-            ),
+            floors::apply_floor_boss_pool
+                .run_if(resource_exists::<ActorRegistry>.and(any_with_component::<SpawnerWave>)),
         );
     }
 }
 
-/// spawns dungeon root
-fn spawn_new_dungeon(
-    mut cmds: Commands,
-    ldtk_project_handles: Res<AspenLevelsetHandles>,
-    dungeon_root: Query<(Entity, &Dungeon)>,
+/// spawns rooms and hallways of a finished `layout` under `dungeon_id`
+fn spawn_layout(
+    cmds: &mut Commands,
+    dungeon_id: Entity,
+    dungeon: &mut Dungeon,
+    dungeon_transform: &Transform,
+    layout: DungeonLayout,
 ) {
-    // TODO: proper dungeon end system with cleanup
-    let level = if let Ok((ent, dungeon)) = dungeon_root.get_single() {
-        cmds.entity(ent).despawn_recursive(); // this happens next frame so dungeon still exists
-        dungeon.settings.level.clone().next_level()
-    } else {
-        components::RoomLevel::Level1
-    };
-
-    let span = 15000.0;
-    let mut rng = rand::thread_rng();
-    let origin = Transform::from_xyz(
-        ensure_tile_pos(rng.gen_range(-span..span)),
-        ensure_tile_pos(rng.gen_range(-span..span)),
-        0.0,
-    );
-    info!("spawning dungeon at {origin:?}");
-
-    cmds.spawn((
-        DungeonContainerBundle {
-            name: "The Aspen Halls".into(),
-            dungeon: Dungeon {
-                settings: DungeonSettings {
-                    level,
-                    // border is applied too each room asset so 0 here
-                    border: 4,
-                    // room placing settings
-                    size: TilemapSize { x: 64, y: 64 },
-                    // TODO: use this but make it working
-                    // tiles_between_rooms: 4,
-                    distribution: RoomDistribution {
-                        small_short: 3,
-                        small_long: 2,
-                        medium_short: 1,
-                        medium_long: 1,
-                        large_short: 0,
-                        large_long: 0,
-                        huge_short: 0,
-                        huge_long: 0,
-                        special: 2,
-                    },
-                    // hallway placing settings/data
-                    hallway_loop_chance: 0.08,
-                },
-                tile_graph: TileGraph {
-                    graph: Graph::new_undirected(),
-                    center_world: origin.translation.truncate(),
-                },
-                room_graph: RoomGraph::default(),
-            },
-            ldtk_project: bevy_ecs_ldtk::LdtkProjectHandle { handle: ldtk_project_handles.default_levels.clone() },
-            spatial: origin,
-            visual: Visibility::Visible
-        },
-        // Position(origin.translation.truncate()),
-    ));
-}
-
-/// creates vec of `RoomPreset` too be built into `DungeonRoomBundles` using  `DungeonSettings` and current progress in the dungeon
-pub fn layout_dungeon(
-    mut cmds: Commands,
-    room_database: Res<DungeonRoomDatabase>,
-    mut dungeon_root: Query<(Entity, &mut Dungeon, &Transform)>,
-    player_query: Query<Entity, With<PlayerSelectedHero>>,
-    mut tp_events: EventWriter<ActorTeleportEvent>,
-) {
-    let (dungon_id, dungeon, dungeon_transform) = dungeon_root.single_mut();
-
-    info!("creating dungeon room blueprints");
-    let mut positioned_presets = create_dungeon_blueprint(dungeon, room_database);
-
-    info!("creating room graph from blueprints");
-    let mut room_graph = RoomGraph::new(positioned_presets.make_contiguous());
-
-    info!("connecting graph");
-    room_graph.connect_graph_randomly();
-
-    info!("computing minimum spanning tree of graph");
-    room_graph.graph = Graph::from_elements(petgraph::algo::min_spanning_tree(&room_graph.graph));
-
-    info!("verifying graph connectivity");
-    room_graph.verify_graph_connections();
-
     info!("spawning rooms");
-    room_graph.node_weights().for_each(|weight| {
-        if let room_graph::RoomGraphNode::Room(bp) = weight {
-            cmds.spawn((
-                DungeonRoomBundle {
-                    name: bp.name.clone().into(),
-                    id: bp.asset_id.clone(),
-                    room: bp.clone(),
-                    spatial: Transform::from_translation(
-                        bp.room_space.min.as_vec2().extend(0.0),
-                    ),
-                    visual: Visibility::Inherited
-                },
-                // Position(bp.room_space.min.as_vec2()),
-            ))
-            .set_parent(dungon_id);
-        };
-    });
-
-    teleport_player_too_start_location(
-        dungeon_transform.translation.truncate(),
-        &player_query,
-        &mut tp_events,
-    );
+    for bp in &layout.rooms {
+        cmds.spawn((
+            DungeonRoomBundle {
+                name: bp.name.clone().into(),
+                id: bp.asset_id.clone(),
+                room: bp.clone(),
+                spatial: bp.transform(),
+                visual: Visibility::Inherited,
+            },
+            // Position(bp.room_space.min.as_vec2()),
+        ))
+        .set_parent(dungeon_id);
+    }
 
     info!("spawning hallways");
-    room_graph.edge_references().for_each(|edge| {
-        let source = room_graph.graph.node_weight(edge.source()).expect("msg");
-        let target = room_graph.graph.node_weight(edge.target()).expect("msg");
-
-        if target.is_exit() && source.is_exit() && source != target {
-            let hallway_name = format!(
-                "Hallway{:?}->{:?}",
-                source.get_node_id(),
-                target.get_node_id()
-            );
+    for hallway in &layout.hallways {
+        let hallway_name = format!(
+            "Hallway{:?}->{:?}",
+            hallway.connected_rooms.0, hallway.connected_rooms.1
+        );
 
-            let start_pos = source.get_nodes_offset();
-            let end_pos = target.get_nodes_offset();
+        cmds.spawn(DungeonHallWayBundle {
+            name: Name::new(hallway_name),
+            hallway: hallway.clone(),
+            spatial: Transform::from_translation(hallway.start_pos.as_vec2().extend(0.0)),
+            visual: Visibility::Inherited,
+        })
+        .set_parent(dungeon_id);
+    }
 
-            cmds.spawn(DungeonHallWayBundle {
-                name: Name::new(hallway_name),
-                hallway: HallWayBlueprint {
-                    start_pos,
-                    end_pos,
-                    distance: edge.weight().length,
-                    node_path: VecDeque::new(),
-                    connected_rooms: (*source.get_node_id(), *target.get_node_id()),
-                    built: false,
-                },
-                spatial: Transform::from_translation(
-                    start_pos.as_vec2().extend(0.0),
-                ),
-                visual: Visibility::Inherited
-            })
-            .set_parent(dungon_id);
-        } else {
-            info!("bad graph edge");
-        }
-    });
+    cmds.entity(dungeon_id)
+        .insert(export::LayoutExport::from_layout(
+            &layout,
+            dungeon_transform.translation.truncate(),
+        ));
 
-    cmds.insert_resource(NextState::Pending(GeneratorState::CompleteHallways));
+    let DungeonLayout {
+        settings,
+        room_graph,
+        tile_graph,
+        start_position,
+        ..
+    } = layout;
+    dungeon.settings = settings;
+    dungeon.room_graph = room_graph;
+    dungeon.tile_graph = tile_graph;
+    dungeon.start_position = start_position;
 }
 
-// TODO: use a quad-tree structure too improve performance?
-// tbh this part of the module is actually pretty quick compared too building the tilegraph
-/// returns random list of room blueprints for a dungeon
-fn create_dungeon_blueprint(
-    dungeon: Mut<Dungeon>,
-    room_database: Res<DungeonRoomDatabase>,
-) -> VecDeque<RoomBlueprint> {
-    let mut room_positions = Vec::new();
-    let progress_level = &dungeon.settings.level;
-
-    // choose presets
-    let mut presets = utils::choose_filler_presets(&dungeon.settings, &room_database);
-    if presets.is_empty() {
-        error!("presets could not be chosen from room database");
-        error!("database {:?}", room_database);
+/// random dungeon position in world, pulls from dungeon rng before layout is created
+fn dungeon_origin(rng: &mut impl Rng) -> Vec2 {
+    let span = 15000.0;
+    Vec2 {
+        x: ensure_tile_pos(rng.gen_range(-span..span)),
+        y: ensure_tile_pos(rng.gen_range(-span..span)),
     }
+}
 
-    // add start and end presets
-    presets.push_back(utils::get_leveled_preset(&room_database.end_rooms, progress_level).unwrap());
-    presets
-        .push_front(utils::get_leveled_preset(&room_database.start_rooms, progress_level).unwrap());
+/// empty space kept around each dungeon so dungeons never touch, in pixels
+const DUNGEON_MARGIN: f32 = TILE_SIZE * 64.0;
 
-    // turn room blueprint
-    let mut positioned_blueprints: VecDeque<RoomBlueprint> = VecDeque::new();
-    for (i, preset) in presets.iter().enumerate() {
-        let rooms_space = if preset.descriptor.rtype == RoomType::DungeonStart {
-            Rect::from_center_size(Vec2::ZERO, preset.size.as_vec2())
-        } else {
-            utils::random_room_positon(&room_positions, preset.size.as_vec2(), &dungeon.settings)
+/// world rect a dungeon with `settings` centered on `origin` covers, grown by `DUNGEON_MARGIN`
+fn dungeon_bounds(origin: Vec2, settings: &DungeonSettings) -> Rect {
+    let size = Vec2 {
+        x: settings.size.x as f32,
+        y: settings.size.y as f32,
+    } * TILE_SIZE;
+    Rect::from_center_size(origin, size).inflate(DUNGEON_MARGIN)
+}
+
+/// moves `origin` along x until a dungeon with `settings` placed there does not overlap any `occupied` rect.
+/// does not pull from dungeon rng so layouts still match exported layouts
+fn free_dungeon_origin(mut origin: Vec2, settings: &DungeonSettings, occupied: &[Rect]) -> Vec2 {
+    loop {
+        let bounds = dungeon_bounds(origin, settings);
+        let Some(blocking) = occupied.iter().find(|f| !f.intersect(bounds).is_empty()) else {
+            return origin;
         };
-        room_positions.push(rooms_space);
-        positioned_blueprints.push_back(RoomBlueprint::from_preset(
-            preset,
-            IVec2 {
-                x: ensure_tile_pos(rooms_space.min.x) as i32,
-                y: ensure_tile_pos(rooms_space.min.y) as i32,
-            },
-            i as u32,
-        ));
+        // always moves right past `blocking` so this can not loop forever
+        origin.x = ((blocking.max.x + bounds.half_size().x) / TILE_SIZE).ceil() * TILE_SIZE;
     }
-
-    positioned_blueprints
 }
 
 /// rounds `element` too nearest multiple of tilesize
@@ -337,28 +249,12 @@ fn ensure_tile_pos(element: f32) -> f32 {
     (element / TILE_SIZE).round() * TILE_SIZE
 }
 
-/// teleports player too the average `Transform` of all entities with `PlayerStartLocation`
-#[allow(clippy::type_complexity)]
+/// teleports player too the dungeons start position
 fn teleport_player_too_start_location(
-    dungeon_center: Vec2,
+    pos: Vec2,
     player_query: &Query<Entity, With<PlayerSelectedHero>>,
     tp_events: &mut EventWriter<ActorTeleportEvent>,
 ) {
-    let start_size = Vec2 { x: 50.0, y: 50.0 };
-
-    let start_loc_rect = Rect {
-        min: Vec2 {
-            x: dungeon_center.x - start_size.x,
-            y: dungeon_center.y - start_size.y,
-        },
-        max: Vec2 {
-            x: dungeon_center.x + start_size.x,
-            y: dungeon_center.y + start_size.y,
-        },
-    };
-
-    let pos = random_point_inside(&start_loc_rect, 1.0).unwrap_or(dungeon_center);
-
     warn!("teleporting player too start location: {}", pos);
     let player_ent = player_query.single();
     tp_events.send(ActorTeleportEvent {
@@ -0,0 +1,128 @@
+use bevy::{prelude::*, utils::HashMap};
+use rand::prelude::Rng;
+
+use crate::game::game_world::dungeonator_v2::components::DungeonSettings;
+
+/// width/height of a `RoomSpatialIndex` cell, in tiles
+const INDEX_CELL_TILES: i32 = 16;
+
+/// random positions tried for a room before every position in the dungeon is checked
+const RANDOM_PLACEMENT_ATTEMPTS: u32 = 64;
+
+/// buckets placed rooms by the grid cells they cover,
+/// overlap checks only look at rooms close too the checked area
+#[derive(Debug, Default)]
+pub struct RoomSpatialIndex {
+    /// rooms covering each cell, in tiles
+    cells: HashMap<IVec2, Vec<IRect>>,
+}
+
+impl RoomSpatialIndex {
+    /// cells covered by `rect`
+    fn covered_cells(rect: IRect) -> impl Iterator<Item = IVec2> {
+        let min = rect.min.div_euclid(IVec2::splat(INDEX_CELL_TILES));
+        let max = (rect.max - 1).div_euclid(IVec2::splat(INDEX_CELL_TILES));
+
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| IVec2 { x, y }))
+    }
+
+    /// adds a placed room too the index
+    pub fn insert(&mut self, rect: IRect) {
+        for cell in Self::covered_cells(rect) {
+            self.cells.entry(cell).or_default().push(rect);
+        }
+    }
+
+    /// does any room in the index overlap `rect`, rooms only touching `rect` dont overlap
+    pub fn overlaps(&self, rect: IRect) -> bool {
+        Self::covered_cells(rect)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .any(|placed| !placed.intersect(rect).is_empty())
+    }
+}
+
+/// places rooms inside the dungeon border,
+/// every room is kept `tiles_between_rooms` away from the others
+#[derive(Debug)]
+pub struct RoomPlacer {
+    /// area rooms can be placed in, dungeon size minus border, in tiles
+    area: IRect,
+    /// gap kept between rooms, in tiles
+    spacing: i32,
+    /// rooms placed so far
+    index: RoomSpatialIndex,
+}
+
+impl RoomPlacer {
+    /// creates placer for a dungeon of `settings.size` centered on the dungeon origin
+    pub fn new(settings: &DungeonSettings) -> Self {
+        let size = IVec2 {
+            x: settings.size.x as i32,
+            y: settings.size.y as i32,
+        };
+        let dungeon = IRect::from_corners(-size / 2, -size / 2 + size);
+
+        Self {
+            area: dungeon.inflate(-(settings.border as i32)),
+            spacing: settings.tiles_between_rooms as i32,
+            index: RoomSpatialIndex::default(),
+        }
+    }
+
+    /// area rooms can be placed in, in tiles
+    pub const fn area(&self) -> IRect {
+        self.area
+    }
+
+    /// tiles needed too fit `room_sizes` with spacing and tiles available inside the border.
+    /// if needed is larger than available the rooms can never fit
+    pub fn required_space(&self, room_sizes: &[IVec2]) -> (i64, i64) {
+        let spacing = IVec2::splat(self.spacing);
+        let tiles = |size: IVec2| i64::from(size.x) * i64::from(size.y);
+
+        let needed = room_sizes.iter().map(|size| tiles(*size + spacing)).sum();
+        (needed, tiles(self.area.size() + spacing))
+    }
+
+    /// is `room` inside the border and far enough from every placed room
+    pub fn fits(&self, room: IRect) -> bool {
+        room.min.cmpge(self.area.min).all()
+            && room.max.cmple(self.area.max).all()
+            && !self.index.overlaps(room.inflate(self.spacing))
+    }
+
+    /// marks `room` as used
+    pub fn place(&mut self, room: IRect) {
+        self.index.insert(room);
+    }
+
+    /// finds free space for a room of `size` tiles.
+    /// random positions are tried first, then every position starting from a random one
+    pub fn find_position(&self, size: IVec2, rng: &mut impl Rng) -> Option<IRect> {
+        let positions = self.area.size() - size + 1;
+        if positions.cmplt(IVec2::ONE).any() {
+            return None;
+        }
+
+        let total = positions.x * positions.y;
+        let room_at = |i: i32| {
+            let min = self.area.min
+                + IVec2 {
+                    x: i % positions.x,
+                    y: i / positions.x,
+                };
+            IRect::from_corners(min, min + size)
+        };
+
+        (0..RANDOM_PLACEMENT_ATTEMPTS)
+            .map(|_| room_at(rng.gen_range(0..total)))
+            .find(|room| self.fits(*room))
+            .or_else(|| {
+                let start = rng.gen_range(0..total);
+                (0..total)
+                    .map(|i| room_at((start + i) % total))
+                    .find(|room| self.fits(*room))
+            })
+    }
+}
@@ -1,20 +1,85 @@
-use bevy::{
-    asset::Assets,
-    prelude::{Commands, IVec2, Res},
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{
+    assets::LdtkExternalLevel,
+    ldtk::{LayerInstance, LdtkJson, Level, TilesetDefinition},
+    prelude::LdtkProject,
 };
-use bevy_ecs_ldtk::{assets::LdtkExternalLevel, prelude::LdtkProject};
 
 use crate::{
     consts::TILE_SIZE,
-    game::game_world::dungeonator_v2::components::{
-        try_get_roomlevel, try_get_roomshape, try_get_roomtype, DungeonRoomDatabase,
-        RoomDescriptor, RoomPreset, RoomShape, RoomType,
+    game::game_world::dungeonator_v2::{
+        components::{
+            try_get_roombiome, try_get_roomlevel, try_get_roomorientations, try_get_roomshape,
+            try_get_roomtype, DungeonRoomDatabase, RoomBiome, RoomCollisionGrid, RoomDescriptor,
+            RoomPreset, RoomShape, RoomType,
+        },
+        tile_graph::TileType,
     },
     loading::assets::AspenLevelsetHandles,
 };
 
+/// problem with a room level found while building the `DungeonRoomDatabase`
+#[derive(Debug, Clone, Reflect, PartialEq, Eq)]
+pub struct RoomValidationIssue {
+    /// identifier of the ldtk level
+    pub level: String,
+    /// field, layer or enum that has the problem
+    pub field: String,
+    /// what is wrong
+    pub reason: String,
+}
+
+impl std::fmt::Display for RoomValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]: {}", self.level, self.field, self.reason)
+    }
+}
+
+/// problems found the last time the `DungeonRoomDatabase` was built
+#[derive(Debug, Clone, Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct RoomDatabaseReport {
+    /// every problem found, levels with problems are left out of the database
+    pub issues: Vec<RoomValidationIssue>,
+    /// levels left out of the database
+    pub skipped_levels: Vec<String>,
+    /// required preset lists that have no rooms, dungeons can not be generated while this has entries
+    pub missing_categories: Vec<String>,
+}
+
+impl RoomDatabaseReport {
+    /// adds `issues` of a skipped level too report
+    pub fn skip_level(&mut self, level: &str, issues: Vec<RoomValidationIssue>) {
+        self.skipped_levels.push(level.to_string());
+        self.issues.extend(issues);
+    }
+
+    /// true if a required preset list is empty
+    pub fn is_fatal(&self) -> bool {
+        !self.missing_categories.is_empty()
+    }
+
+    /// writes report too log, missing categories are errors
+    pub fn log(&self) {
+        for issue in &self.issues {
+            warn!("invalid room level {issue}");
+        }
+        if !self.skipped_levels.is_empty() {
+            warn!(
+                "skipped {} invalid room levels: {:?}",
+                self.skipped_levels.len(),
+                self.skipped_levels
+            );
+        }
+        for category in &self.missing_categories {
+            error!("room database has no {category}, dungeons can not be generated");
+        }
+    }
+}
+
 /// maps `level_assets` too a `DungeonRoomDatabase`
-/// dungeons are filtered into vecs based on level custom data
+/// dungeons are filtered into vecs based on level custom data.
+/// invalid levels are skipped and added too the `RoomDatabaseReport`
 pub fn build_room_presets(
     mut cmds: Commands,
     map_projects: Res<AspenLevelsetHandles>,
@@ -26,138 +91,287 @@ pub fn build_room_presets(
         .expect("project not found")
         .as_parent();
 
-    let mut dungeon_database = DungeonRoomDatabase {
-        hideouts: Vec::new(),
-        start_rooms: Vec::new(),
-        end_rooms: Vec::new(),
-        special_rooms: Vec::new(),
-        small_short_rooms: Vec::new(),
-        small_long_rooms: Vec::new(),
-        medium_short_rooms: Vec::new(),
-        medium_long_rooms: Vec::new(),
-        large_short_rooms: Vec::new(),
-        large_long_rooms: Vec::new(),
-        huge_short_rooms: Vec::new(),
-        huge_long_rooms: Vec::new(),
-    };
+    let mut dungeon_database = DungeonRoomDatabase::default();
+    let mut report = RoomDatabaseReport::default();
 
     dungeon_project
         .iter_external_levels(&ldtk_levels)
         .for_each(|level_def| {
-            // TODO: if positioning is fixed this is uneeded?
-            validate_room_size(level_def).expect("all rooms should be correct size");
-
-            let field_instances = level_def.field_instances();
-            let room_shape = try_get_roomshape(field_instances)
-                .expect("No size ident on room definiton. Check Ldtk Editor for errors");
-            let room_type = try_get_roomtype(field_instances)
-                .expect("No type ident on room definition. Check Ldtk Editor for errors");
-            let room_level = try_get_roomlevel(field_instances)
-                .expect("No level ident on room definition. Check Ldtk Editor for errors");
-
-            let tile_types = &dungeon_project
-                .json_data()
-                .defs
-                .enums
-                .iter()
-                .find(|f| f.identifier == "CollisionType")
-                .expect("CollisionType enum must exist");
-
-            let _exit_definition = tile_types
-                .values
-                .iter()
-                .find(|f| f.id == "RoomExit")
-                .expect("RoomExit type must exist");
-
-            let building_layer = &level_def
-                .layer_instances()
-                .iter()
-                .find(|f| f.identifier == "Building_Layer")
-                .expect("no entity layer on this level");
-
-            // let _layer_width = building_layer.c_wid * TILE_SIZE as i32;
-            let layer_height = building_layer.c_hei * TILE_SIZE as i32;
-
-            let building_tiles = &building_layer.grid_tiles;
-
-            let exit_tile_positions: Vec<IVec2> = // Vec::new();
-            building_tiles.iter().enumerate()
-                .filter(|(_idx, tile)| tile.t == 2 )
-                .map(|(_idx, tile)| {
-
-                //TODO: this value need too be calculated with a bottom origin
-                // ldtk gives offset with top origin
-                let tile_px_y = layer_height - tile.px.y - 32;
-                let tile_px_x = tile.px.x;
-
-                IVec2 {
-                        x: tile_px_x,
-                        y: tile_px_y,
-                    }
-                })
-                .collect();
-
-            let room = RoomPreset {
-                name: level_def.identifier().to_string(),
-                room_asset_id: level_def.iid().clone().into(),
-                size: IVec2::new(*level_def.px_wid(), *level_def.px_hei()),
-                exits: exit_tile_positions,
-                descriptor: RoomDescriptor {
-                    shape: room_shape.clone(),
-                    level: room_level,
-                    rtype: room_type.clone(),
-                },
-            };
-
-            match &room_type {
-                RoomType::Hideout => dungeon_database.hideouts.push(room),
-                RoomType::DungeonStart => dungeon_database.start_rooms.push(room),
-                RoomType::DungeonEnd => dungeon_database.end_rooms.push(room),
-                RoomType::Normal | RoomType::Special | RoomType::MiniBoss => match room_shape {
-                    RoomShape::NonStandard => dungeon_database.special_rooms.push(room),
-                    RoomShape::SmallShort => dungeon_database.small_short_rooms.push(room),
-                    RoomShape::SmallLong => dungeon_database.small_long_rooms.push(room),
-                    RoomShape::MediumShort => dungeon_database.medium_short_rooms.push(room),
-                    RoomShape::MediumLong => dungeon_database.medium_long_rooms.push(room),
-                    RoomShape::LargeShort => dungeon_database.large_short_rooms.push(room),
-                    RoomShape::LargeLong => dungeon_database.large_long_rooms.push(room),
-                    RoomShape::HugeShort => dungeon_database.huge_short_rooms.push(room),
-                    RoomShape::HugeLong => dungeon_database.huge_long_rooms.push(room),
-                },
+            if let Err(issues) =
+                dungeon_database.add_level(dungeon_project.json_data(), level_def.raw())
+            {
+                report.skip_level(level_def.identifier(), issues);
             }
         });
 
+    report.missing_categories = dungeon_database.missing_categories();
+    report.log();
+
     cmds.insert_resource(dungeon_database);
+    cmds.insert_resource(report);
+}
+
+impl DungeonRoomDatabase {
+    /// creates `RoomPreset` from `level_def` and adds it too the matching preset list.
+    ///
+    /// # Errors
+    /// returns every problem with `level_def` if it can not be used as a room,
+    /// the level is not added
+    pub fn add_level(
+        &mut self,
+        project: &LdtkJson,
+        level_def: &Level,
+    ) -> Result<(), Vec<RoomValidationIssue>> {
+        let room = room_preset_from_level(project, level_def)?;
+
+        match &room.descriptor.rtype {
+            RoomType::Hideout => self.hideouts.push(room),
+            RoomType::DungeonStart => self.start_rooms.push(room),
+            RoomType::DungeonEnd => self.end_rooms.push(room),
+            RoomType::Secret => self.secret_rooms.push(room),
+            RoomType::Normal | RoomType::Special | RoomType::MiniBoss => {
+                match room.descriptor.shape {
+                    RoomShape::NonStandard => self.special_rooms.push(room),
+                    RoomShape::SmallShort => self.small_short_rooms.push(room),
+                    RoomShape::SmallLong => self.small_long_rooms.push(room),
+                    RoomShape::MediumShort => self.medium_short_rooms.push(room),
+                    RoomShape::MediumLong => self.medium_long_rooms.push(room),
+                    RoomShape::LargeShort => self.large_short_rooms.push(room),
+                    RoomShape::LargeLong => self.large_long_rooms.push(room),
+                    RoomShape::HugeShort => self.huge_short_rooms.push(room),
+                    RoomShape::HugeLong => self.huge_long_rooms.push(room),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// names of preset lists a dungeon needs that have no rooms
+    pub fn missing_categories(&self) -> Vec<String> {
+        [
+            ("start_rooms", &self.start_rooms),
+            ("end_rooms", &self.end_rooms),
+        ]
+        .into_iter()
+        .filter(|(_, presets)| presets.is_empty())
+        .map(|(category, _)| category.to_string())
+        .collect()
+    }
+}
+
+/// creates `RoomPreset` from `level_def`, collecting every problem that stops it from being a room
+fn room_preset_from_level(
+    project: &LdtkJson,
+    level_def: &Level,
+) -> Result<RoomPreset, Vec<RoomValidationIssue>> {
+    let issue = |field: &str, reason: &str| RoomValidationIssue {
+        level: level_def.identifier.clone(),
+        field: field.to_string(),
+        reason: reason.to_string(),
+    };
+    let mut issues = Vec::new();
+
+    // TODO: if positioning is fixed this is uneeded?
+    if let Err(reason) = validate_room_size(level_def) {
+        issues.push(issue("size", &reason));
+    }
+
+    let field_instances = &level_def.field_instances;
+    let room_shape = try_get_roomshape(field_instances);
+    if room_shape.is_none() {
+        issues.push(issue("IdentSize", "missing or unknown room size ident"));
+    }
+    let room_type = try_get_roomtype(field_instances);
+    if room_type.is_none() {
+        issues.push(issue("IdentType", "missing or unknown room type ident"));
+    }
+    let room_level = try_get_roomlevel(field_instances);
+    if room_level.is_none() {
+        issues.push(issue("IdentLevel", "missing or unknown room level ident"));
+    }
+    let room_biome = try_get_roombiome(field_instances).unwrap_or_else(|value| {
+        issues.push(issue("IdentBiome", &format!("unknown room biome {value}")));
+        RoomBiome::default()
+    });
+    let orientations = try_get_roomorientations(field_instances).unwrap_or_else(|value| {
+        issues.push(issue(
+            "IdentOrientations",
+            &format!("unknown room orientation {value}"),
+        ));
+        Vec::new()
+    });
+    let (orientations, unsupported): (Vec<_>, Vec<_>) = orientations
+        .into_iter()
+        .partition(|orientation| orientation.keeps_art_upright());
+    if !unsupported.is_empty() {
+        warn!(
+            "room level {} lists orientations the room art cant be drawn with, ignoring {unsupported:?}",
+            level_def.identifier
+        );
+    }
+
+    match project
+        .defs
+        .enums
+        .iter()
+        .find(|f| f.identifier == "CollisionType")
+    {
+        None => issues.push(issue("CollisionType", "enum is missing from ldtk project")),
+        Some(tile_types) if !tile_types.values.iter().any(|f| f.id == "RoomExit") => {
+            issues.push(issue("CollisionType", "enum has no RoomExit value"));
+        }
+        Some(_) => {}
+    }
+
+    let building_layer = level_def
+        .layer_instances
+        .as_ref()
+        .and_then(|layers| layers.iter().find(|f| f.identifier == "Building_Layer"));
+    if building_layer.is_none() {
+        issues.push(issue("Building_Layer", "level has no building layer"));
+    }
+
+    let (Some(room_shape), Some(room_type), Some(room_level), Some(building_layer)) =
+        (room_shape, room_type, room_level, building_layer)
+    else {
+        return Err(issues);
+    };
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+
+    // let _layer_width = building_layer.c_wid * TILE_SIZE as i32;
+    let layer_height = building_layer.c_hei * TILE_SIZE as i32;
+
+    let building_tiles = &building_layer.grid_tiles;
+    let building_tileset = project
+        .defs
+        .tilesets
+        .iter()
+        .find(|f| Some(f.uid) == building_layer.tileset_def_uid);
+
+    let exit_tile_positions: Vec<IVec2> = // Vec::new();
+    building_tiles.iter().enumerate()
+        .filter(|(_idx, tile)| tile.t == 2 )
+        .map(|(_idx, tile)| {
+
+        //TODO: this value need too be calculated with a bottom origin
+        // ldtk gives offset with top origin
+        let tile_px_y = layer_height - tile.px.y - 32;
+        let tile_px_x = tile.px.x;
+
+        IVec2 {
+                x: tile_px_x,
+                y: tile_px_y,
+            }
+        })
+        .collect();
+
+    Ok(RoomPreset {
+        name: level_def.identifier.clone(),
+        room_asset_id: level_def.iid.clone().into(),
+        size: IVec2::new(level_def.px_wid, level_def.px_hei),
+        exits: exit_tile_positions,
+        collision: extract_collision_grid(building_layer, building_tileset),
+        orientations,
+        descriptor: RoomDescriptor {
+            shape: room_shape,
+            level: room_level,
+            rtype: room_type,
+            biome: room_biome,
+        },
+    })
+}
+
+/// creates `RoomCollisionGrid` from tiles in `layer` using the collision tags from `tileset`
+fn extract_collision_grid(
+    layer: &LayerInstance,
+    tileset: Option<&TilesetDefinition>,
+) -> RoomCollisionGrid {
+    let width = layer.c_wid as u32;
+    let height = layer.c_hei as u32;
+    let mut grid = RoomCollisionGrid::new(width, height);
+
+    for tile in layer.grid_tiles.iter().chain(&layer.auto_layer_tiles) {
+        let x = (tile.px.x / layer.grid_size) as u32;
+        // ldtk rows start at the top, grid rows start at the bottom
+        let y = height
+            .saturating_sub(1)
+            .saturating_sub((tile.px.y / layer.grid_size) as u32);
+
+        let tile_type = tileset.map_or(TileType::Floor, |tileset| {
+            collision_tile_type(
+                tileset
+                    .enum_tags
+                    .iter()
+                    .filter(|f| f.tile_ids.contains(&tile.t))
+                    .map(|f| f.enum_value_id.as_str()),
+            )
+        });
+
+        // layers can overlap, walls win over everything else
+        if tile_priority(tile_type) >= tile_priority(grid.get(x, y)) {
+            grid.set(x, y, tile_type);
+        }
+    }
+
+    grid
+}
+
+/// converts a tiles `CollisionType` tags too a `TileType`,
+/// matches what `process_tile_enum_tags` spawns for the tile
+fn collision_tile_type<'a>(tags: impl Iterator<Item = &'a str>) -> TileType {
+    let mut tile_type = TileType::Floor;
+    for tag in tags {
+        if tag.starts_with("Collide") || tag.starts_with("DoubleWall") {
+            return TileType::Wall;
+        } else if tag == "RoomExit" {
+            tile_type = TileType::RoomExit;
+        } else if tag == "HallwayBoundry" && tile_type != TileType::RoomExit {
+            tile_type = TileType::Unused;
+        }
+    }
+    tile_type
+}
+
+/// which tile type should be kept when tiles overlap
+const fn tile_priority(tile_type: TileType) -> u8 {
+    match tile_type {
+        TileType::Wall => 4,
+        TileType::RoomExit => 3,
+        TileType::Floor => 2,
+        TileType::Hallway => 1,
+        TileType::Unused => 0,
+    }
 }
 
 /// ensures roomsize for certain rooms are correct tilesize
-fn validate_room_size(
-    level_def: bevy_ecs_ldtk::prelude::ldtk::loaded_level::LoadedLevel<'_>,
-) -> Result<(), String> {
+fn validate_room_size(level_def: &Level) -> Result<(), String> {
     // check if room assets are right size,
     // if they arent the correct size we get annoying panics elsewhere
     if ["DungeonStartL1", "TestingHalls"]
         .iter()
-        .all(|f| f != level_def.identifier())
-        && ((level_def.px_wid() / TILE_SIZE as i32) % 2 != 0
-            || (level_def.px_hei() / TILE_SIZE as i32) % 2 != 0)
+        .all(|f| *f != level_def.identifier)
+        && ((level_def.px_wid / TILE_SIZE as i32) % 2 != 0
+            || (level_def.px_hei / TILE_SIZE as i32) % 2 != 0)
     {
         let msg = format!(
             "Dungeon filler room MUST be even number of tiles in size for x AND y: {}",
-            level_def.identifier()
+            level_def.identifier
         );
         return Err(msg);
     }
 
     if ["DungeonStartL1", "SomeGarbageSoClippyIsComplacent"]
         .iter()
-        .all(|f| f == level_def.identifier())
-        && ((level_def.px_wid() / TILE_SIZE as i32) / 2 == 0
-            || (level_def.px_hei() / TILE_SIZE as i32) / 2 == 0)
+        .all(|f| *f == level_def.identifier)
+        && ((level_def.px_wid / TILE_SIZE as i32) / 2 == 0
+            || (level_def.px_hei / TILE_SIZE as i32) / 2 == 0)
     {
         let msg = format!(
             "ONLY Dungeon Start room MUST be odd number of tiles in size for x AND y: {}",
-            level_def.identifier()
+            level_def.identifier
         );
         return Err(msg);
     }
@@ -1,24 +1,17 @@
-use bevy::{prelude::*, utils::HashSet};
-use petgraph::{prelude::NodeIndex, Graph};
-use rand::seq::IteratorRandom;
+use bevy::{math::FloatOrd, prelude::*, utils::HashSet};
+use petgraph::{
+    prelude::{EdgeRef, NodeIndex},
+    Graph, Undirected,
+};
+use rand::{seq::IteratorRandom, Rng};
 
 use crate::game::game_world::dungeonator_v2::{
-    components::{RoomBlueprint, RoomID},
+    components::{RoomBlueprint, RoomID, RoomType},
     hallways::SiblingCount,
     room_graph::{RoomGraph, RoomGraphEdge, RoomGraphNode},
 };
 
 impl RoomGraph {
-    /// outputs room graph into graphviz file
-    pub fn dump_too_file(&self) {
-        let graph =
-            petgraph::dot::Dot::with_config(&self.graph, &[petgraph::dot::Config::EdgeNoLabel]);
-
-        if let Err(e) = std::fs::write("dungeon.dot", format!("{graph:?}")) {
-            warn!("error saving dot file: {}", e);
-        }
-    }
-
     /// creates new roomgraph from given '`RoomBlueprints`'
     pub fn new(presets: &[RoomBlueprint]) -> Self {
         let mut graph = Self {
@@ -147,9 +140,8 @@ impl RoomGraph {
         }
     }
 
-    /// randomly connects exits inside roomgraph,
-    pub fn connect_graph_randomly(&mut self) {
-        let mut rng = rand::thread_rng();
+    /// randomly connects exits inside roomgraph using `rng`
+    pub fn connect_graph_randomly(&mut self, rng: &mut impl Rng) {
         let room_graph = &mut self.graph;
         let graph_copy = room_graph.clone();
 
@@ -166,7 +158,7 @@ impl RoomGraph {
             let Some(first_exit) = exit_nodes
                 .iter()
                 .filter(|f| !exits_connected.contains(&f.0))
-                .choose(&mut rng)
+                .choose(rng)
             else {
                 break;
             };
@@ -224,6 +216,158 @@ impl RoomGraph {
         }
     }
 
+    /// adds back the shortest exit too exit edges from `connected` that are missing from this graph,
+    /// `loop_chance` is the fraction of missing edges that get added as loops.
+    /// rooms joined by a loop always keep 1 of there `SiblingCount` exits free
+    ///
+    /// `connected` must have the same nodes as this graph, like the graph from before the spanning tree was made
+    pub fn add_loop_edges(
+        &mut self,
+        connected: &Graph<RoomGraphNode, RoomGraphEdge, Undirected>,
+        loop_chance: f32,
+    ) {
+        let mut discarded: Vec<(NodeIndex, NodeIndex, f32)> = connected
+            .edge_references()
+            .filter(|edge| {
+                connected[edge.source()].is_exit()
+                    && connected[edge.target()].is_exit()
+                    && self.find_edge(edge.source(), edge.target()).is_none()
+            })
+            .map(|edge| (edge.source(), edge.target(), edge.weight().length))
+            .collect();
+        // shortest loops first
+        discarded.sort_by_key(|(_, _, length)| FloatOrd(*length));
+
+        // rounded so small chances can still add no loops at all
+        let loop_amount = (discarded.len() as f32 * loop_chance.clamp(0.0, 1.0)).round() as usize;
+        let mut loops_added = 0;
+
+        for (exit1_id, exit2_id, length) in discarded {
+            if loops_added >= loop_amount {
+                break;
+            }
+
+            if !self.exit_can_loop(exit1_id)
+                || !self.exit_can_loop(exit2_id)
+                || self.rooms_are_connected(exit1_id, exit2_id)
+            {
+                continue;
+            }
+
+            self.add_edge(exit1_id, exit2_id, RoomGraphEdge { length });
+            loops_added += 1;
+        }
+
+        info!("added {} hallway loops too graph", loops_added);
+    }
+
+    /// adds `secret_room` too graph and connects its closest exit too the closest free exit
+    /// of a normal or mini boss room, returns false if no exit was free.
+    ///
+    /// secret rooms are left out of the spanning tree so this single hallway is the only way in
+    pub fn attach_secret_room(&mut self, secret_room: &RoomBlueprint) -> bool {
+        let host_rooms: HashSet<RoomID> = self
+            .get_rooms()
+            .into_iter()
+            .filter(|room| matches!(room.descriptor.rtype, RoomType::Normal | RoomType::MiniBoss))
+            .map(|room| room.id)
+            .collect();
+        let host_exits: Vec<NodeIndex> = self
+            .node_indices()
+            .filter(|node| {
+                self[*node].is_exit()
+                    && host_rooms.contains(self[*node].get_node_id())
+                    && self.exit_is_free(*node)
+            })
+            .collect();
+
+        let room = self.add_node(RoomGraphNode::Room(secret_room.clone()));
+        let secret_exits: Vec<NodeIndex> = secret_room
+            .exits
+            .iter()
+            .map(|exit| {
+                let exit_node = self.add_node(RoomGraphNode::Exit {
+                    exit: exit.clone(),
+                    brothers: SiblingCount(secret_room.exits.len() as u32),
+                });
+                self.add_edge(room, exit_node, RoomGraphEdge { length: 0.0 });
+                exit_node
+            })
+            .collect();
+
+        let graph: &Self = self;
+        let closest = secret_exits
+            .iter()
+            .flat_map(|secret| host_exits.iter().map(move |host| (*secret, *host)))
+            .min_by_key(|(secret, host)| {
+                graph[*secret]
+                    .get_nodes_offset()
+                    .distance_squared(graph[*host].get_nodes_offset())
+            });
+        let Some((secret_exit, host_exit)) = closest else {
+            return false;
+        };
+
+        // doing sqrt so it is acceptable too save
+        let distance = self[secret_exit]
+            .get_nodes_offset()
+            .distance_squared(self[host_exit].get_nodes_offset()) as f32;
+        self.add_edge(
+            host_exit,
+            secret_exit,
+            RoomGraphEdge {
+                length: distance.sqrt(),
+            },
+        );
+        true
+    }
+
+    /// true if exit has no hallway
+    pub(super) fn exit_is_free(&self, exit_id: NodeIndex) -> bool {
+        // exits only have an edge too there room when unconnected
+        self.node_weight(exit_id)
+            .is_some_and(RoomGraphNode::is_exit)
+            && self.edges(exit_id).count() == 1
+    }
+
+    /// true if exit is free and a loop through it still leaves its room a free sibling exit,
+    /// so loops never use the last exit secret rooms and rule repairs can attach too
+    fn exit_can_loop(&self, exit_id: NodeIndex) -> bool {
+        let Some(RoomGraphNode::Exit { exit, brothers }) = self.node_weight(exit_id) else {
+            return false;
+        };
+
+        let connected_siblings = self
+            .node_indices()
+            .filter(|node| match &self[*node] {
+                RoomGraphNode::Exit { exit: sibling, .. } => {
+                    sibling.parent == exit.parent && !self.exit_is_free(*node)
+                }
+                RoomGraphNode::Room(_) => false,
+            })
+            .count() as u32;
+
+        self.exit_is_free(exit_id) && connected_siblings + 1 < brothers.0
+    }
+
+    /// true if exits parent rooms are the same room or already share a hallway
+    fn rooms_are_connected(&self, exit1_id: NodeIndex, exit2_id: NodeIndex) -> bool {
+        let room1 = *self[exit1_id].get_node_id();
+        let room2 = *self[exit2_id].get_node_id();
+        if room1 == room2 {
+            return true;
+        }
+
+        self.edge_references().any(|edge| {
+            let source = &self[edge.source()];
+            let target = &self[edge.target()];
+            source.is_exit()
+                && target.is_exit()
+                && ((*source.get_node_id() == room1 && *target.get_node_id() == room2)
+                    || (*source.get_node_id() == room2 && *target.get_node_id() == room1))
+        })
+    }
+
     /// returns references to room blueprints inside roomgraph
     pub fn get_rooms(&self) -> Vec<&RoomBlueprint> {
         self.node_weights()
@@ -8,6 +8,8 @@ use crate::game::game_world::{
 
 /// implementation for roomgraph
 pub mod r#impl;
+/// placement rules for finished room graphs
+pub mod rules;
 
 #[derive(Debug, Deref, DerefMut, Clone, Default, Reflect)]
 /// rooms and hallways for a given dungeon
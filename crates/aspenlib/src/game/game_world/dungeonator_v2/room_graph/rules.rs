@@ -0,0 +1,402 @@
+use std::{cmp::Reverse, collections::VecDeque};
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use petgraph::prelude::{EdgeIndex, EdgeRef, NodeIndex};
+
+use crate::game::game_world::dungeonator_v2::{
+    components::{RoomID, RoomType},
+    room_graph::{RoomGraph, RoomGraphEdge, RoomGraphNode},
+};
+
+/// constraint the finished room graph of a dungeon must follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, serde::Deserialize, serde::Serialize)]
+pub enum PlacementRule {
+    /// end room must be at least this many rooms away from the start room.
+    /// repaired by moving the end room onto a room far enough from the start room
+    MinEndDistance(u32),
+    /// every path from the start room too the end room must pass through a mini boss room.
+    /// repaired by moving the end room onto a mini boss room and removing its other hallways.
+    /// does nothing if the dungeon has no mini boss rooms
+    MiniBossGuardsEnd,
+    /// special rooms must be dead ends with only 1 hallway
+    SpecialRoomsAreLeaves,
+}
+
+impl PlacementRule {
+    /// rules used when a floor does not list its own
+    pub fn default_rules() -> Vec<Self> {
+        vec![
+            Self::MinEndDistance(3),
+            Self::MiniBossGuardsEnd,
+            Self::SpecialRoomsAreLeaves,
+        ]
+    }
+
+    /// normal or mini boss rooms a dungeon needs for this rule too be possible
+    pub const fn rooms_needed(self) -> usize {
+        match self {
+            Self::MinEndDistance(hops) => hops.saturating_sub(1) as usize,
+            Self::MiniBossGuardsEnd | Self::SpecialRoomsAreLeaves => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for PlacementRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MinEndDistance(hops) => write!(f, "end room at least {hops} rooms from start"),
+            Self::MiniBossGuardsEnd => write!(f, "mini boss guards end room"),
+            Self::SpecialRoomsAreLeaves => write!(f, "special rooms are dead ends"),
+        }
+    }
+}
+
+/// rule that a room graph broke and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleViolation {
+    /// rule that was broken
+    pub rule: PlacementRule,
+    /// what about the graph broke the rule
+    pub reason: String,
+}
+
+impl std::fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' failed: {}", self.rule, self.reason)
+    }
+}
+
+impl RoomGraph {
+    /// checks every rule in `rules`, repairing the graph where a rule can be repaired
+    ///
+    /// # Errors
+    /// returns the first rule that is still broken after repairs
+    pub fn enforce_rules(&mut self, rules: &[PlacementRule]) -> Result<(), RuleViolation> {
+        for rule in rules {
+            if let Err(violation) = self.check_rule(*rule) {
+                if self.repair_rule(*rule) {
+                    info!("repaired room graph, {violation}");
+                }
+            }
+        }
+
+        rules.iter().try_for_each(|rule| self.check_rule(*rule))
+    }
+
+    /// checks if room graph follows `rule`
+    ///
+    /// # Errors
+    /// returns `RuleViolation` describing how `rule` was broken
+    pub fn check_rule(&self, rule: PlacementRule) -> Result<(), RuleViolation> {
+        let violation = |reason: String| Err(RuleViolation { rule, reason });
+        let connections = self.room_connections();
+
+        let (Some(start), Some(end)) = (
+            self.rooms_of_type(&RoomType::DungeonStart).first().copied(),
+            self.rooms_of_type(&RoomType::DungeonEnd).first().copied(),
+        ) else {
+            return violation("dungeon has no start or end room".to_string());
+        };
+
+        match rule {
+            PlacementRule::MinEndDistance(min_hops) => {
+                match room_distance(&connections, start, end, &HashSet::new()) {
+                    Some(hops) if hops >= min_hops => Ok(()),
+                    Some(hops) => violation(format!("end room is {hops} rooms from start")),
+                    None => violation("end room can not be reached from start".to_string()),
+                }
+            }
+            PlacementRule::MiniBossGuardsEnd => {
+                let mini_bosses: HashSet<RoomID> = self
+                    .rooms_of_type(&RoomType::MiniBoss)
+                    .into_iter()
+                    .collect();
+                if mini_bosses.is_empty() {
+                    debug!("no mini boss rooms in dungeon, skipping guard rule");
+                    return Ok(());
+                }
+
+                if room_distance(&connections, start, end, &mini_bosses).is_some() {
+                    violation("end room can be reached without passing a mini boss".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            PlacementRule::SpecialRoomsAreLeaves => {
+                for room in self.rooms_of_type(&RoomType::Special) {
+                    let hallways = connections.get(&room).map_or(0, HashSet::len);
+                    if hallways != 1 {
+                        return violation(format!(
+                            "special room {room:?} connects too {hallways} rooms"
+                        ));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// tries too change graph so it follows `rule`, returns true if `rule` is now followed
+    fn repair_rule(&mut self, rule: PlacementRule) -> bool {
+        match rule {
+            PlacementRule::SpecialRoomsAreLeaves => {
+                for room in self.rooms_of_type(&RoomType::Special) {
+                    while let Some(hallway) = self.removable_hallway(room) {
+                        self.remove_edge(hallway);
+                    }
+                }
+                self.check_rule(rule).is_ok()
+            }
+            PlacementRule::MiniBossGuardsEnd => {
+                self.guard_end_room();
+                self.check_rule(rule).is_ok()
+            }
+            PlacementRule::MinEndDistance(min_hops) => {
+                self.move_end_room_away(min_hops);
+                self.check_rule(rule).is_ok()
+            }
+        }
+    }
+
+    /// moves the end room onto the room farthest from the start room,
+    /// mini boss rooms at least `min_hops - 1` rooms from the start room are used first
+    fn move_end_room_away(&mut self, min_hops: u32) {
+        let hosts: Vec<RoomID> = self
+            .rooms_of_type(&RoomType::Normal)
+            .into_iter()
+            .chain(self.rooms_of_type(&RoomType::MiniBoss))
+            .collect();
+        let hosts = self.end_room_hosts(&hosts, min_hops);
+        self.reattach_end_room(&hosts);
+    }
+
+    /// moves the end room onto the mini boss room farthest from the start room
+    fn guard_end_room(&mut self) {
+        let mini_bosses = self.rooms_of_type(&RoomType::MiniBoss);
+        let hosts = self.end_room_hosts(&mini_bosses, 0);
+        self.reattach_end_room(&hosts);
+    }
+
+    /// rooms of `candidates` the end room can hang from and still be at least `min_hops` from start.
+    /// mini bosses come first, then rooms already next too the end room, then farthest from start
+    fn end_room_hosts(&self, candidates: &[RoomID], min_hops: u32) -> Vec<RoomID> {
+        let (Some(start), Some(end)) = (
+            self.rooms_of_type(&RoomType::DungeonStart).first().copied(),
+            self.rooms_of_type(&RoomType::DungeonEnd).first().copied(),
+        ) else {
+            return Vec::new();
+        };
+        let connections = self.room_connections();
+        let end_neighbours = connections.get(&end).cloned().unwrap_or_default();
+        let mini_bosses: HashSet<RoomID> = self
+            .rooms_of_type(&RoomType::MiniBoss)
+            .into_iter()
+            .collect();
+        // rooms only reachable through the end room can not hold it
+        let through_end: HashSet<RoomID> = [end].into_iter().collect();
+
+        let mut hosts: Vec<(RoomID, u32)> = candidates
+            .iter()
+            .filter_map(|room| {
+                room_distance(&connections, start, *room, &through_end).map(|hops| (*room, hops))
+            })
+            .filter(|(_, hops)| hops + 1 >= min_hops)
+            .collect();
+        hosts.sort_by_key(|(room, hops)| {
+            (
+                !mini_bosses.contains(room),
+                !end_neighbours.contains(room),
+                Reverse(*hops),
+            )
+        });
+
+        hosts.into_iter().map(|(room, _)| room).collect()
+    }
+
+    /// connects the end room too the first room in `hosts` with a free exit,
+    /// then removes its other hallways that can be removed without splitting the dungeon
+    fn reattach_end_room(&mut self, hosts: &[RoomID]) {
+        let Some(end) = self.rooms_of_type(&RoomType::DungeonEnd).first().copied() else {
+            return;
+        };
+        let end_neighbours = self
+            .room_connections()
+            .get(&end)
+            .cloned()
+            .unwrap_or_default();
+
+        let Some(host) = hosts
+            .iter()
+            .copied()
+            .find(|room| end_neighbours.contains(room) || !self.free_exits(*room).is_empty())
+        else {
+            debug!("no room with a free exit can hold the end room");
+            return;
+        };
+
+        if !end_neighbours.contains(&host) {
+            // end rooms often use every exit, free one up for the new hallway.
+            // a dead end room can drop its only hallway since the new one replaces it
+            if self.free_exits(end).is_empty() {
+                let end_hallways = self.hallways_of(end);
+                let hallway = self
+                    .removable_hallway(end)
+                    .or_else(|| (end_hallways.len() == 1).then(|| end_hallways[0].0));
+                if let Some(hallway) = hallway {
+                    self.remove_edge(hallway);
+                }
+            }
+
+            let host_exits = self.free_exits(host);
+            let closest = self
+                .free_exits(end)
+                .into_iter()
+                .flat_map(|end_exit| {
+                    host_exits
+                        .iter()
+                        .map(move |host_exit| (end_exit, *host_exit))
+                })
+                .min_by_key(|(end_exit, host_exit)| {
+                    self[*end_exit]
+                        .get_nodes_offset()
+                        .distance_squared(self[*host_exit].get_nodes_offset())
+                });
+            let Some((end_exit, host_exit)) = closest else {
+                debug!("end room has no free exit too connect too {host:?}");
+                return;
+            };
+
+            let length = self[end_exit]
+                .get_nodes_offset()
+                .as_vec2()
+                .distance(self[host_exit].get_nodes_offset().as_vec2());
+            self.add_edge(end_exit, host_exit, RoomGraphEdge { length });
+        }
+
+        // edge indices change when an edge is removed so hallways are searched again each time
+        while let Some(hallway) = self.other_end_hallway(end, host) {
+            self.remove_edge(hallway);
+        }
+    }
+
+    /// hallway of `end` too a room that is not `host`,
+    /// that can be removed without splitting the dungeon
+    fn other_end_hallway(&self, end: RoomID, host: RoomID) -> Option<EdgeIndex> {
+        self.hallways_of(end)
+            .into_iter()
+            .filter(|(_, other, _)| *other != host)
+            .map(|(id, _, _)| id)
+            .find(|id| !self.splits_dungeon(*id))
+    }
+
+    /// exits of `room` without a hallway
+    fn free_exits(&self, room: RoomID) -> Vec<NodeIndex> {
+        self.node_indices()
+            .filter(|node| *self[*node].get_node_id() == room && self.exit_is_free(*node))
+            .collect()
+    }
+
+    /// longest hallway of `room` that can be removed without splitting the dungeon,
+    /// `None` if room has 1 or less hallways
+    fn removable_hallway(&self, room: RoomID) -> Option<EdgeIndex> {
+        let mut hallways = self.hallways_of(room);
+        if hallways.len() <= 1 {
+            return None;
+        }
+        hallways.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        hallways
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .find(|id| !self.splits_dungeon(*id))
+    }
+
+    /// every hallway of `room` with the room on its other side and its length
+    fn hallways_of(&self, room: RoomID) -> Vec<(EdgeIndex, RoomID, f32)> {
+        self.edge_references()
+            .filter_map(|edge| {
+                let source = &self[edge.source()];
+                let target = &self[edge.target()];
+                if !source.is_exit() || !target.is_exit() {
+                    return None;
+                }
+
+                let (source, target) = (*source.get_node_id(), *target.get_node_id());
+                let other = if source == room {
+                    target
+                } else if target == room {
+                    source
+                } else {
+                    return None;
+                };
+                Some((edge.id(), other, edge.weight().length))
+            })
+            .collect()
+    }
+
+    /// true if removing `hallway` leaves part of the dungeon unreachable
+    fn splits_dungeon(&self, hallway: EdgeIndex) -> bool {
+        let mut test_graph = self.graph.clone();
+        test_graph.remove_edge(hallway);
+        petgraph::algo::connected_components(&test_graph) != 1
+    }
+
+    /// which rooms each room shares a hallway with
+    fn room_connections(&self) -> HashMap<RoomID, HashSet<RoomID>> {
+        let mut connections: HashMap<RoomID, HashSet<RoomID>> = HashMap::new();
+
+        for edge in self.edge_references() {
+            let source = &self[edge.source()];
+            let target = &self[edge.target()];
+            if !source.is_exit() || !target.is_exit() {
+                continue;
+            }
+
+            let (room1, room2) = (*source.get_node_id(), *target.get_node_id());
+            connections.entry(room1).or_default().insert(room2);
+            connections.entry(room2).or_default().insert(room1);
+        }
+
+        connections
+    }
+
+    /// ids of every room with `rtype`
+    fn rooms_of_type(&self, rtype: &RoomType) -> Vec<RoomID> {
+        self.node_weights()
+            .filter_map(|node| match node {
+                RoomGraphNode::Room(room) if room.descriptor.rtype == *rtype => Some(room.id),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// amount of hallways between `start` and `end` without entering any `blocked` room
+fn room_distance(
+    connections: &HashMap<RoomID, HashSet<RoomID>>,
+    start: RoomID,
+    end: RoomID,
+    blocked: &HashSet<RoomID>,
+) -> Option<u32> {
+    let mut visited: HashSet<RoomID> = HashSet::new();
+    let mut queue = VecDeque::from([(start, 0)]);
+    visited.insert(start);
+
+    while let Some((room, hops)) = queue.pop_front() {
+        if room == end {
+            return Some(hops);
+        }
+
+        for next in connections.get(&room).into_iter().flatten() {
+            if blocked.contains(next) || !visited.insert(*next) {
+                continue;
+            }
+            queue.push_back((*next, hops + 1));
+        }
+    }
+
+    None
+}
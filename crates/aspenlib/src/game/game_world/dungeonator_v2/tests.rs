@@ -0,0 +1,731 @@
+use std::sync::OnceLock;
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_tilemap::prelude::TilemapSize;
+use petgraph::{
+    prelude::{EdgeRef, NodeIndex},
+    Graph,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    consts::TILE_SIZE,
+    game::game_world::dungeonator_v2::{
+        components::{
+            DungeonRoomDatabase, DungeonSeed, DungeonSettings, HallwayWidths, RoomBiome,
+            RoomBlueprint, RoomCollisionGrid, RoomDescriptor, RoomDistribution, RoomLevel,
+            RoomOrientation, RoomPreset, RoomShape, RoomType,
+        },
+        hallways::{
+            hallway_builder::hallway_floor_tiles, HallWayBlueprint, MAX_HALLWAY_WIDTH,
+            MIN_HALLWAY_WIDTH,
+        },
+        layout::{generate_layout, DungeonLayout, LayoutError},
+        placement::RoomPlacer,
+        room_graph::{rules::PlacementRule, RoomGraph},
+        tile_graph::{
+            r#impl::{room_footprints, world_cell},
+            TileType,
+        },
+    },
+};
+
+/// layouts checked when `DUNGEON_LAYOUT_CASES` is not set
+const DEFAULT_CASES: u64 = 2000;
+
+/// seeds checked by the layout tests.
+/// `DUNGEON_LAYOUT_SEED` replays a single seed, `DUNGEON_LAYOUT_CASES` changes how many seeds are checked
+fn test_seeds() -> Vec<u64> {
+    let env_number = |name: &str| std::env::var(name).ok().and_then(|f| f.parse::<u64>().ok());
+
+    env_number("DUNGEON_LAYOUT_SEED").map_or_else(
+        || (0..env_number("DUNGEON_LAYOUT_CASES").unwrap_or(DEFAULT_CASES)).collect(),
+        |seed| vec![seed],
+    )
+}
+
+/// room preset of `size` tiles with a wall ring around its floor,
+/// exits are placed in the middle of 1 too 4 random sides
+fn synthetic_preset(
+    name: String,
+    size: UVec2,
+    descriptor: RoomDescriptor,
+    rng: &mut impl Rng,
+) -> RoomPreset {
+    let mut collision = RoomCollisionGrid::new(size.x, size.y);
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let edge = x == 0 || y == 0 || x == size.x - 1 || y == size.y - 1;
+            collision.set(
+                x,
+                y,
+                if edge {
+                    TileType::Wall
+                } else {
+                    TileType::Floor
+                },
+            );
+        }
+    }
+
+    let sides = [
+        UVec2::new(size.x / 2, 0),
+        UVec2::new(size.x / 2, size.y - 1),
+        UVec2::new(0, size.y / 2),
+        UVec2::new(size.x - 1, size.y / 2),
+    ];
+    let exit_count = rng.gen_range(1..=sides.len());
+    let exits: Vec<UVec2> = sides.choose_multiple(rng, exit_count).copied().collect();
+    for exit in &exits {
+        collision.set(exit.x, exit.y, TileType::RoomExit);
+    }
+
+    let mut orientations = vec![RoomOrientation::Identity];
+    orientations.extend(
+        [
+            RoomOrientation::Rotate90,
+            RoomOrientation::Rotate180,
+            RoomOrientation::Rotate270,
+            RoomOrientation::MirrorX,
+            RoomOrientation::MirrorY,
+        ]
+        .into_iter()
+        .filter(|_| rng.gen_bool(0.5)),
+    );
+
+    RoomPreset {
+        descriptor,
+        room_asset_id: name.clone().into(),
+        name,
+        size: size.as_ivec2() * TILE_SIZE as i32,
+        exits: exits
+            .iter()
+            .map(|f| f.as_ivec2() * TILE_SIZE as i32)
+            .collect(),
+        collision,
+        orientations,
+    }
+}
+
+/// 1 too 3 presets of `size` tiles for a room database bucket
+fn synthetic_bucket(
+    label: &str,
+    size: UVec2,
+    shape: &RoomShape,
+    rtype: &RoomType,
+    rng: &mut impl Rng,
+) -> Vec<RoomPreset> {
+    (0..rng.gen_range(1..=3))
+        .map(|i| {
+            let descriptor = RoomDescriptor {
+                shape: shape.clone(),
+                level: RoomLevel::Level1,
+                rtype: rtype.clone(),
+                biome: RoomBiome::Crypt,
+            };
+            synthetic_preset(format!("{label}_{i}"), size, descriptor, rng)
+        })
+        .collect()
+}
+
+/// room database with random presets for every bucket the tests use,
+/// rooms are much smaller than real rooms so thousands of layouts are quick too make
+fn synthetic_database(rng: &mut impl Rng) -> DungeonRoomDatabase {
+    let normal = RoomType::Normal;
+    DungeonRoomDatabase {
+        start_rooms: synthetic_bucket(
+            "start",
+            UVec2::new(10, 10),
+            &RoomShape::NonStandard,
+            &RoomType::DungeonStart,
+            rng,
+        ),
+        end_rooms: synthetic_bucket(
+            "end",
+            UVec2::new(12, 12),
+            &RoomShape::NonStandard,
+            &RoomType::DungeonEnd,
+            rng,
+        ),
+        // mini bosses share the special bucket like the real database
+        special_rooms: [
+            synthetic_bucket(
+                "special",
+                UVec2::new(9, 9),
+                &RoomShape::NonStandard,
+                &RoomType::Special,
+                rng,
+            ),
+            synthetic_bucket(
+                "mini_boss",
+                UVec2::new(10, 10),
+                &RoomShape::NonStandard,
+                &RoomType::MiniBoss,
+                rng,
+            ),
+        ]
+        .concat(),
+        secret_rooms: synthetic_bucket(
+            "secret",
+            UVec2::new(6, 6),
+            &RoomShape::NonStandard,
+            &RoomType::Secret,
+            rng,
+        ),
+        small_short_rooms: synthetic_bucket(
+            "small_short",
+            UVec2::new(8, 8),
+            &RoomShape::SmallShort,
+            &normal,
+            rng,
+        ),
+        small_long_rooms: synthetic_bucket(
+            "small_long",
+            UVec2::new(8, 12),
+            &RoomShape::SmallLong,
+            &normal,
+            rng,
+        ),
+        medium_short_rooms: synthetic_bucket(
+            "medium_short",
+            UVec2::new(12, 12),
+            &RoomShape::MediumShort,
+            &normal,
+            rng,
+        ),
+        medium_long_rooms: synthetic_bucket(
+            "medium_long",
+            UVec2::new(12, 18),
+            &RoomShape::MediumLong,
+            &normal,
+            rng,
+        ),
+        ..default()
+    }
+}
+
+/// random settings for `seed` using the default placement rules,
+/// dungeon size is picked so the chosen rooms always have space
+fn synthetic_settings(seed: u64, rng: &mut impl Rng) -> DungeonSettings {
+    // enough normal rooms that the end room can be kept away from the start room
+    let distribution = RoomDistribution {
+        small_short: rng.gen_range(2..=4),
+        small_long: rng.gen_range(1..=3),
+        medium_short: rng.gen_range(0..=3),
+        medium_long: rng.gen_range(0..=2),
+        special: rng.gen_range(0..=2),
+        secret: rng.gen_range(1..=2),
+        ..default()
+    };
+    let border: u32 = rng.gen_range(1..=4);
+    let tiles_between_rooms: u32 = rng.gen_range(1..=5);
+
+    // longest side of each room, start and end room are always added
+    let rooms = [
+        (distribution.small_short, 8),
+        (distribution.small_long, 12),
+        (distribution.medium_short, 12),
+        (distribution.medium_long, 18),
+        (distribution.special, 10),
+        (distribution.secret, 6),
+        (1, 10),
+        (1, 12),
+    ];
+    let needed: u32 = rooms
+        .iter()
+        .map(|(count, side)| count.unsigned_abs() * (side + tiles_between_rooms).pow(2))
+        .sum();
+    let side = (1..=needed * 3)
+        .find(|side| side * side >= needed * 3)
+        .unwrap_or(1)
+        + border * 2;
+
+    DungeonSettings {
+        seed: DungeonSeed(seed),
+        room_levels: vec![RoomLevel::Level1],
+        border,
+        size: TilemapSize { x: side, y: side },
+        tiles_between_rooms,
+        distribution,
+        hallway_loop_chance: rng.gen_range(0.0..0.5),
+        hallway_widths: HallwayWidths {
+            default: rng.gen_range(MIN_HALLWAY_WIDTH..=3),
+            room_types: vec![(
+                RoomType::DungeonEnd,
+                rng.gen_range(MIN_HALLWAY_WIDTH..=MAX_HALLWAY_WIDTH),
+            )],
+        },
+        placement_rules: PlacementRule::default_rules(),
+        ..default()
+    }
+}
+
+/// database, settings and layout created for a single test seed
+struct SeedLayout {
+    /// seed everything was created from
+    seed: u64,
+    /// synthetic room database the layout picked presets from
+    database: DungeonRoomDatabase,
+    /// synthetic settings the layout was made with
+    settings: DungeonSettings,
+    /// generated layout
+    layout: Result<DungeonLayout, LayoutError>,
+}
+
+/// creates database, settings and layout for `seed`
+fn layout_for_seed(seed: u64) -> SeedLayout {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let database = synthetic_database(&mut rng);
+    let settings = synthetic_settings(seed, &mut rng);
+    let layout = generate_layout(&settings, &database, &mut rng);
+
+    SeedLayout {
+        seed,
+        database,
+        settings,
+        layout,
+    }
+}
+
+/// layouts for every test seed, generated once and shared by each invariant test
+fn seed_layouts() -> &'static [SeedLayout] {
+    static LAYOUTS: OnceLock<Vec<SeedLayout>> = OnceLock::new();
+    LAYOUTS.get_or_init(|| test_seeds().into_iter().map(layout_for_seed).collect())
+}
+
+/// runs `check` on every generated layout and fails listing each seed it rejected.
+/// seeds without a layout are reported by `every_seed_creates_a_layout`
+fn assert_layouts(
+    invariant: &str,
+    check: impl Fn(&SeedLayout, &DungeonLayout) -> Result<(), String>,
+) {
+    let failures: Vec<String> = seed_layouts()
+        .iter()
+        .filter_map(|seed_layout| {
+            let layout = seed_layout.layout.as_ref().ok()?;
+            check(seed_layout, layout)
+                .err()
+                .map(|reason| format!("seed {}: {reason}", seed_layout.seed))
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} layouts broke '{invariant}', replay one with DUNGEON_LAYOUT_SEED=<seed>\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+/// rooms are inside the dungeon border and `tiles_between_rooms` away from each other
+fn check_room_spacing(settings: &DungeonSettings, layout: &DungeonLayout) -> Result<(), String> {
+    let area = RoomPlacer::new(settings).area();
+    let spacing = settings.tiles_between_rooms as i32;
+    let rects: Vec<IRect> = layout
+        .rooms
+        .iter()
+        .map(|f| {
+            IRect::from_corners(
+                f.room_space.min / TILE_SIZE as i32,
+                f.room_space.max / TILE_SIZE as i32,
+            )
+        })
+        .collect();
+
+    for (i, (room, rect)) in layout.rooms.iter().zip(&rects).enumerate() {
+        if rect.min.cmplt(area.min).any() || rect.max.cmpgt(area.max).any() {
+            return Err(format!(
+                "room {} {rect:?} is outside dungeon border {area:?}",
+                room.name
+            ));
+        }
+
+        for (other, other_rect) in layout.rooms.iter().zip(&rects).skip(i + 1) {
+            if !rect.intersect(*other_rect).is_empty() {
+                return Err(format!("room {} overlaps room {}", room.name, other.name));
+            }
+            if !rect.inflate(spacing).intersect(*other_rect).is_empty() {
+                return Err(format!(
+                    "room {} is closer than {spacing} tiles too room {}",
+                    room.name, other.name
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// every `RoomDistribution` count is placed, with a single start and end room
+fn check_distribution(settings: &DungeonSettings, layout: &DungeonLayout) -> Result<(), String> {
+    let placed = |rtype: RoomType, shape: Option<RoomShape>| {
+        layout
+            .rooms
+            .iter()
+            .filter(|f| {
+                f.descriptor.rtype == rtype
+                    && shape.as_ref().is_none_or(|s| *s == f.descriptor.shape)
+            })
+            .count()
+    };
+    let distribution = &settings.distribution;
+
+    let counts = [
+        (
+            "small short",
+            distribution.small_short,
+            placed(RoomType::Normal, Some(RoomShape::SmallShort)),
+        ),
+        (
+            "small long",
+            distribution.small_long,
+            placed(RoomType::Normal, Some(RoomShape::SmallLong)),
+        ),
+        (
+            "medium short",
+            distribution.medium_short,
+            placed(RoomType::Normal, Some(RoomShape::MediumShort)),
+        ),
+        (
+            "medium long",
+            distribution.medium_long,
+            placed(RoomType::Normal, Some(RoomShape::MediumLong)),
+        ),
+        (
+            "special",
+            distribution.special,
+            placed(RoomType::Special, None) + placed(RoomType::MiniBoss, None),
+        ),
+        (
+            "secret",
+            distribution.secret,
+            placed(RoomType::Secret, None),
+        ),
+        ("start", 1, placed(RoomType::DungeonStart, None)),
+        ("end", 1, placed(RoomType::DungeonEnd, None)),
+    ];
+
+    for (name, wanted, placed) in counts {
+        if usize::try_from(wanted) != Ok(placed) {
+            return Err(format!(
+                "{placed} {name} rooms were placed but {wanted} were wanted"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// every room can be walked too from the start room through hallways
+fn check_reachability(layout: &DungeonLayout) -> Result<(), String> {
+    let start = layout
+        .rooms
+        .iter()
+        .find(|f| f.descriptor.rtype == RoomType::DungeonStart)
+        .ok_or_else(|| "layout has no start room".to_string())?;
+
+    let mut reached = HashSet::new();
+    reached.insert(start.id);
+    let mut frontier = vec![start.id];
+    while let Some(room) = frontier.pop() {
+        for hallway in layout.hallways.iter().filter(|f| !f.node_path.is_empty()) {
+            let (a, b) = hallway.connected_rooms;
+            let next = if a == room {
+                b
+            } else if b == room {
+                a
+            } else {
+                continue;
+            };
+            if reached.insert(next) {
+                frontier.push(next);
+            }
+        }
+    }
+
+    layout
+        .rooms
+        .iter()
+        .find(|f| !reached.contains(&f.id))
+        .map_or(Ok(()), |room| {
+            Err(format!(
+                "room {} {:?} can not be reached from the start room",
+                room.name, room.id
+            ))
+        })
+}
+
+/// hallways start and end on exits of the rooms they connect and never cross a room
+fn check_hallways(database: &DungeonRoomDatabase, layout: &DungeonLayout) -> Result<(), String> {
+    let presets: Vec<&RoomPreset> = [
+        &database.start_rooms,
+        &database.end_rooms,
+        &database.special_rooms,
+        &database.secret_rooms,
+        &database.small_short_rooms,
+        &database.small_long_rooms,
+        &database.medium_short_rooms,
+        &database.medium_long_rooms,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let placed = layout
+        .rooms
+        .iter()
+        .map(|room| {
+            presets
+                .iter()
+                .find(|f| f.room_asset_id == room.asset_id)
+                .map(|preset| (*preset, room))
+                .ok_or_else(|| format!("room {} has no preset", room.name))
+        })
+        .collect::<Result<Vec<(&RoomPreset, &RoomBlueprint)>, String>>()?;
+    let footprints = room_footprints(&placed);
+
+    let tile_graph = &layout.tile_graph;
+    let tile_cell =
+        |tile: UVec2| world_cell(tile_graph.get_tiles_translation_world(&layout.settings, tile));
+
+    for hallway in layout.hallways.iter().filter(|f| !f.node_path.is_empty()) {
+        let (source, target) = hallway.connected_rooms;
+        let ends = [
+            (hallway.node_path.front(), source),
+            (hallway.node_path.back(), target),
+        ];
+        for (end, room_id) in ends {
+            let Some(tile) = end.map(|f| tile_graph[*f].tile) else {
+                continue;
+            };
+            let cell = tile_cell(tile);
+            let on_exit = footprints.get(&cell) == Some(&TileType::RoomExit)
+                && layout
+                    .rooms
+                    .iter()
+                    .filter(|f| f.id == room_id)
+                    .flat_map(|f| &f.exits)
+                    .any(|f| world_cell(f.position.as_vec2()) == cell);
+            if !on_exit {
+                return Err(format!(
+                    "hallway {:?} ends on tile {tile} which is not an exit of room {room_id:?}",
+                    hallway.connected_rooms
+                ));
+            }
+        }
+
+        for tile in hallway_floor_tiles(tile_graph, &hallway.node_path, hallway.width) {
+            if matches!(
+                footprints.get(&tile_cell(tile)),
+                Some(TileType::Floor | TileType::Wall)
+            ) {
+                return Err(format!(
+                    "hallway {:?} tile {tile} is inside a room",
+                    hallway.connected_rooms
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// room graph follows every placement rule the layout was made with
+fn check_placement_rules(layout: &DungeonLayout) -> Result<(), String> {
+    layout.settings.placement_rules.iter().try_for_each(|rule| {
+        layout
+            .room_graph
+            .check_rule(*rule)
+            .map_err(|f| f.to_string())
+    })
+}
+
+/// secret rooms have exactly 1 hallway and it is hidden behind a breakable wall
+fn check_secret_rooms(layout: &DungeonLayout) -> Result<(), String> {
+    for room in layout
+        .rooms
+        .iter()
+        .filter(|f| f.descriptor.rtype == RoomType::Secret)
+    {
+        let hallways: Vec<&HallWayBlueprint> = layout
+            .hallways
+            .iter()
+            .filter(|f| f.connected_rooms.0 == room.id || f.connected_rooms.1 == room.id)
+            .collect();
+        if hallways.len() != 1 {
+            return Err(format!(
+                "secret room {} has {} hallways",
+                room.name,
+                hallways.len()
+            ));
+        }
+        if hallways[0].hidden_exit.is_none() {
+            return Err(format!(
+                "hallway too secret room {} has no hidden exit",
+                room.name
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// rebuilds the room graph for the rooms of `layout` with every loop it can fit,
+/// rooms joined by a loop must still have a free exit
+fn check_loop_exits(seed: u64, layout: &DungeonLayout) -> Result<(), String> {
+    let rooms: Vec<RoomBlueprint> = layout
+        .rooms
+        .iter()
+        .filter(|room| room.descriptor.rtype != RoomType::Secret)
+        .cloned()
+        .collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut room_graph = RoomGraph::new(&rooms);
+    room_graph.connect_graph_randomly(&mut rng);
+    let connected_graph = room_graph.graph.clone();
+    room_graph.graph = Graph::from_elements(petgraph::algo::min_spanning_tree(&room_graph.graph));
+    room_graph.verify_graph_connections();
+
+    let tree_edges: HashSet<(NodeIndex, NodeIndex)> = room_graph
+        .edge_references()
+        .map(|edge| (edge.source(), edge.target()))
+        .collect();
+    room_graph.add_loop_edges(&connected_graph, 1.0);
+
+    let loop_exits = room_graph
+        .edge_references()
+        .filter(|edge| !tree_edges.contains(&(edge.source(), edge.target())))
+        .flat_map(|edge| [edge.source(), edge.target()]);
+    for loop_exit in loop_exits {
+        let room = room_graph[loop_exit].get_node_id();
+        let has_free_exit = room_graph.node_indices().any(|node| {
+            room_graph[node].is_exit()
+                && room_graph[node].get_node_id() == room
+                && room_graph.edges(node).count() == 1
+        });
+        if !has_free_exit {
+            return Err(format!(
+                "hallway loop used the last free exit of room {room:?}"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// every seed creates a layout, generation errors are listed with there seed
+#[test]
+fn every_seed_creates_a_layout() {
+    let failures: Vec<String> = seed_layouts()
+        .iter()
+        .filter_map(|f| {
+            f.layout
+                .as_ref()
+                .err()
+                .map(|error| format!("seed {}: {error}", f.seed))
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} seeds could not create a layout, replay one with DUNGEON_LAYOUT_SEED=<seed>\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+/// rooms never overlap and keep there spacing inside the border
+#[test]
+fn rooms_are_spaced_inside_border() {
+    assert_layouts("room spacing", |f, layout| {
+        check_room_spacing(&f.settings, layout)
+    });
+}
+
+/// every room the distribution asks for is placed
+#[test]
+fn room_distribution_is_placed() {
+    assert_layouts("room distribution", |f, layout| {
+        check_distribution(&f.settings, layout)
+    });
+}
+
+/// every room can be walked too from the start room
+#[test]
+fn rooms_are_reachable_from_start() {
+    assert_layouts("room reachability", |_, layout| check_reachability(layout));
+}
+
+/// hallways run exit too exit without entering a room
+#[test]
+fn hallways_join_exits_outside_rooms() {
+    assert_layouts("hallway placement", |f, layout| {
+        check_hallways(&f.database, layout)
+    });
+}
+
+/// room graphs follow every placement rule,
+/// mini boss rooms must show up in some layouts or there guard rule is never checked
+#[test]
+fn placement_rules_hold() {
+    assert_layouts("placement rules", |_, layout| check_placement_rules(layout));
+
+    let layouts = seed_layouts();
+    let has_mini_boss = layouts
+        .iter()
+        .filter_map(|f| f.layout.as_ref().ok())
+        .any(|f| {
+            f.rooms
+                .iter()
+                .any(|room| room.descriptor.rtype == RoomType::MiniBoss)
+        });
+    assert!(
+        layouts.len() == 1 || has_mini_boss,
+        "no layout placed a mini boss room so its placement rule was never checked"
+    );
+}
+
+/// hallway loops never use more exits than there rooms `SiblingCount` allows
+#[test]
+fn hallway_loops_respect_sibling_exits() {
+    assert_layouts("hallway loop exits", |f, layout| {
+        check_loop_exits(f.seed, layout)
+    });
+}
+
+/// secret rooms keep there single hidden hallway
+#[test]
+fn secret_rooms_have_one_hidden_hallway() {
+    assert_layouts("secret rooms", |_, layout| check_secret_rooms(layout));
+}
+
+/// layouts are repeatable from there seed
+#[test]
+fn same_seed_creates_same_layout() {
+    for seed in test_seeds().into_iter().take(20) {
+        let first = layout_for_seed(seed).layout;
+        let second = layout_for_seed(seed).layout;
+
+        let hallway_paths = |layout: &DungeonLayout| {
+            layout
+                .hallways
+                .iter()
+                .map(|f| f.node_path.clone())
+                .collect::<Vec<_>>()
+        };
+        match (first, second) {
+            (Ok(first), Ok(second)) => {
+                assert_eq!(first.rooms, second.rooms, "seed {seed}: rooms differ");
+                assert_eq!(
+                    hallway_paths(&first),
+                    hallway_paths(&second),
+                    "seed {seed}: hallways differ"
+                );
+            }
+            (first, second) => assert_eq!(
+                first.err(),
+                second.err(),
+                "seed {seed}: only one layout failed"
+            ),
+        }
+    }
+}
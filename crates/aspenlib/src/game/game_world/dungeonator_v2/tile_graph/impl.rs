@@ -1,285 +1,123 @@
-use avian2d::prelude::Collider;
-use bevy::prelude::*;
-use bevy_ecs_ldtk::GridCoords;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_ecs_tilemap::prelude::TilemapSize;
-
-use image::{ImageBuffer, Rgba};
-use petgraph::{prelude::NodeIndex, visit::IntoNodeReferences, Graph, Undirected};
-use std::fs;
+use petgraph::{prelude::NodeIndex, visit::IntoNodeReferences};
 
 use crate::{
     consts::TILE_SIZE,
-    game::game_world::{
-        components::RoomExitTile,
-        dungeonator_v2::{
-            components::{Dungeon, DungeonSettings},
-            ensure_tile_pos,
-            hallways::hallway_builder::distance_tiles,
-            tile_graph::{TileGraph, TileGraphEdge, TileGraphNode, TileType},
-        },
-        RoomBoundryTile,
+    game::game_world::dungeonator_v2::{
+        components::{DungeonSettings, RoomBlueprint, RoomPreset},
+        ensure_tile_pos,
+        hallways::MAX_HALLWAY_WIDTH,
+        tile_graph::{TileGraph, TileGraphEdge, TileGraphNode, TileType},
     },
 };
 
-#[allow(clippy::type_complexity)]
-/// check each tile position for a tile in gameworld and add node too list
-pub fn populate_tilegraph(
-    dungeon: &mut Dungeon,
-    tile_query: Query<
-        (
-            &GlobalTransform,
-            Option<&Collider>,
-            Option<&RoomExitTile>,
-            Option<&RoomBoundryTile>,
-        ),
-        With<GridCoords>,
-    >,
-) {
-    let tile_graph = &mut dungeon.tile_graph;
-    let settings = &dungeon.settings;
-
-    assert!(!tile_query.is_empty(), "no tiles too map");
-    for x in 0..=settings.size.x {
-        for y in 0..=settings.size.y {
-            let coords = UVec2 { x, y };
-
-            // Calculate the global translation of the tile based on dungeon position and coords
-            let tile_translation = tile_graph.get_tiles_translation_world(settings, coords);
-            let tile_on_position = tile_occupies_position(&tile_query, tile_translation);
-
-            tile_graph.add_node(TileGraphNode {
+/// empty tiles kept around the rooms on each side of the tilegraph,
+/// hallways leaving exits on the outside of the dungeon need space too turn
+const TILEGRAPH_MARGIN_TILES: f32 = (MAX_HALLWAY_WIDTH + 2) as f32;
+
+/// creates sparse tilegraph covering all `rooms`, only tiles a hallway could be placed on become nodes.
+/// room tiles are read from each rooms `RoomCollisionGrid`
+///
+/// `settings.size` is updated too the size of the created graph
+pub fn build_tilegraph(
+    settings: &mut DungeonSettings,
+    rooms: &[(&RoomPreset, &RoomBlueprint)],
+) -> TileGraph {
+    let (size, center_world) = actual_map_tile_size(rooms);
+    settings.size = size;
+
+    let mut tile_graph = TileGraph {
+        center_world,
+        ..Default::default()
+    };
+
+    info!("collecting room footprints");
+    let rows = settings.size.y as usize + 1;
+    let last_tile = IVec2::new(settings.size.x as i32, settings.size.y as i32);
+    // every tile of the graph in x then y order, tiles no room covers stay unused
+    let mut tiles = vec![TileType::Unused; (settings.size.x as usize + 1) * rows];
+    let origin_cell = world_cell(tile_graph.get_tiles_translation_world(settings, UVec2::ZERO));
+    for (cell, tile) in room_footprints(rooms) {
+        let coords = cell - origin_cell;
+        if coords.cmpge(IVec2::ZERO).all() && coords.cmple(last_tile).all() {
+            tiles[coords.x as usize * rows + coords.y as usize] = tile;
+        }
+    }
+
+    for (index, tile) in tiles.into_iter().enumerate() {
+        let coords = UVec2 {
+            x: (index / rows) as u32,
+            y: (index % rows) as u32,
+        };
+
+        if tile.can_be_hallway() {
+            tile_graph.add_tile(TileGraphNode {
                 tile: coords,
-                data: tile_on_position,
+                data: tile,
             });
+        } else {
+            tile_graph.room_tiles.insert(coords, tile);
         }
     }
 
     info!("connecting adjectent nodes in graph");
-    connect_adjacent_nodes(dungeon);
+    connect_adjacent_nodes(&mut tile_graph);
     info!("finished connecting adjacent nodes");
 
-    // TODO: for RoomNode in graph, for n.min.x..n.max.x
-    // does not add borders too rooms for paths
-    // let graph_copy = tile_graph.clone();
-    // let room_graph = &dungeon.room_graph;
-    // for x in 0..=settings.size.x {
-    //     for y in 0..=settings.size.y {
-    //         let coords = UVec2 { x, y };
-    //         let tile_on_position = TileType::Unused;
-
-    //         tile_graph.add_node(TileGraphNode {
-    //             tile: coords,
-    //             data: tile_on_position,
-    //         });
-    //     }
-    // }
-    // let room_areas: Vec<(UVec2, UVec2)> = room_graph.get_rooms().iter().map(|f| {
-    //     (
-    //         tile_graph.get_position_tile_coords(settings, f.position.as_vec2()),
-    //         tile_graph.get_position_tile_coords(settings, (f.position + f.size).as_vec2()),
-    //     )
-    // }).collect();
-
-    // for room in room_areas {
-    //     for x in room.0.x..=room.1.x {
-    //         for y in room.0.y..=room.1.y {
-    //             let coords = UVec2 { x, y };
-    //             // Calculate the actual translation of the tile based on dungeon position
-    //             let tile_translation = tile_graph.get_tiles_translation_world(settings, coords);
-    //             let tile_on_position = tile_occupies_position(&tile_query, tile_translation);
-    //             let tile_id = tile_graph.get_node_at_coord(coords).expect("msg");
-
-    //             if tile_on_position.is_wall() {
-    //                 // remove all edges of this tile
-    //                 let edges = graph_copy.edges(tile_id.id());
-    //                 for edge in edges {
-    //                     tile_graph.remove_edge(edge.id());
-    //                 }
-    //             }
-
-    //             if !tile_on_position.is_unused() {
-    //                 // get tile idx and mutably change value too proper tiletype
-    //                 tile_graph.node_weight_mut(tile_id).expect("msg").data = tile_on_position
-    //             }
-    //         }
-    //     }
-    // }
-}
-
-// clippy really doesnt like this code
-// TODO: find a better solution for this garbage
-#[allow(clippy::type_complexity)]
-#[allow(clippy::unnecessary_find_map)]
-/// does position have a tile
-pub fn tile_occupies_position(
-    tiles: &Query<
-        (
-            &GlobalTransform,
-            Option<&Collider>,
-            Option<&RoomExitTile>,
-            Option<&RoomBoundryTile>,
-        ),
-        With<GridCoords>,
-    >,
-    position: Vec2,
-) -> TileType {
-    tiles
-        .iter()
-        .filter(|(transform, ..)| transform.translation().truncate() == position)
-        .find_map(
-            |(_, exit, collider, roomboundry)| match (exit, collider, roomboundry) {
-                (Some(_), None, None) => Some(TileType::Wall),
-                (None, Some(_), None) => Some(TileType::RoomExit),
-                (None, None, Some(_)) => Some(TileType::Unused),
-                (None, None, None) => Some(TileType::Floor),
-                _ => panic!("tile had exit collider and room boundry or some combination of these"),
-            },
-        )
-        .unwrap_or(TileType::Unused)
+    tile_graph
 }
 
-//TODO: performance-: precalculate empty spaces using room dimensions, then check inside room dimensions for walkability
-// it might be faster too do this 'destructively'.
-// I.e. create edges as tilegraph is being made, for every tile, then remove all edges for tiles that are unwalkable
-
-/// connects adjacent nodes in graph
-/// 1.041611 seconds
-/// 0.874678 | 0.677892 | 0.452816 with `last_node` sort
-pub fn connect_adjacent_nodes(dungeon: &mut Dungeon) {
-    // let references: Vec<(NodeIndex, &TileGraphNode)> = graph.node_references().collect();
-    let mut edges: Vec<(NodeIndex, NodeIndex, TileGraphEdge)> = Vec::new();
-    let tilegraph = &mut dungeon.tile_graph;
-
-    info!("filtering adjacent nodes in tilegraph for walkability");
-    let mut walkable_nodes: Vec<NodeIndex> = tilegraph
-        .node_indices()
-        .filter(|f| tilegraph[*f].data.can_be_hallway())
-        .collect::<Vec<NodeIndex>>();
-
-    let tile_center: UVec2 = <TilemapSize as Into<UVec2>>::into(dungeon.settings.size) / 2;
-
-    let mut last_node = tile_center;
-    // sort by distance from center, this makes checking for
-    walkable_nodes.sort_by(|a, b| {
-        let TileGraphNode { tile: tile_a, .. } = tilegraph[*a];
-        let TileGraphNode { tile: tile_b, .. } = tilegraph[*b];
-        let distance_a = distance_tiles(last_node, tile_a);
-        let distance_b = distance_tiles(last_node, tile_b);
-        last_node = if distance_a > distance_b {
-            tile_a
-        } else {
-            tile_b
-        };
-        distance_a.cmp(&distance_b)
-    });
-
-    info!("pairing walkable nodes");
-    for (i, current_idx) in walkable_nodes.iter().enumerate() {
-        let current = &tilegraph[*current_idx];
-        let mut connect_count = 0;
-
-        if connect_count == 4 {
-            continue;
-        }
+/// tile type for every tile covered by a room, keyed by `world_cell`
+pub fn room_footprints(rooms: &[(&RoomPreset, &RoomBlueprint)]) -> HashMap<IVec2, TileType> {
+    let mut room_tiles = HashMap::new();
 
-        for other_idx in walkable_nodes.iter().skip(i + 1) {
-            let other = &tilegraph[*other_idx];
-
-            if is_adjacent(current.tile, other.tile) {
-                connect_count += 1;
-                edges.push((
-                    *current_idx,
-                    *other_idx,
-                    TileGraphEdge {
-                        cost: calculate_weight(
-                            &(*current_idx, current),
-                            &(*other_idx, other),
-                            tilegraph,
-                        ),
-                    },
-                ));
+    for (preset, blueprint) in rooms {
+        let room_cell = world_cell(blueprint.room_space.min.as_vec2());
+        let grid = preset.collision.oriented(blueprint.orientation);
+        for x in 0..grid.width {
+            for y in 0..grid.height {
+                room_tiles.insert(room_cell + UVec2 { x, y }.as_ivec2(), grid.get(x, y));
             }
         }
     }
 
-    info!("extending graph with edges");
-    tilegraph.extend_with_edges(edges);
+    room_tiles
 }
 
-/// calculates edge weight for 2 graph nodes
-pub fn calculate_weight(
-    current: &(NodeIndex, &TileGraphNode),
-    other: &(NodeIndex, &TileGraphNode),
-    graph: &Graph<TileGraphNode, TileGraphEdge, Undirected, u32>,
-) -> f32 {
-    let (current_index, current_node) = current;
-    let (other_index, other_node) = other;
-
-    assert!(
-        current_index != other_index,
-        "calculating weights between same tiles not allowed"
-    );
-
-    // TODO: maybe change this assert too only check 1 tile distance
-    assert!(
-        distance_tiles(current_node.tile, other_node.tile) == 1
-            || distance_tiles(current_node.tile, other_node.tile) == 2,
-        "tiles are not adjacent: {}",
-        distance_tiles(current_node.tile, other_node.tile)
-    );
-
-    let multiplier = if graph.neighbors(*current_index).count() < 4 {
-        5.0
-    } else {
-        1.0
-    };
-
-    let inner: f32 = match current_node.data {
-        TileType::Floor | TileType::Wall => 99.0,
-        TileType::Hallway => 10.0,
-        TileType::RoomExit => 1.0,
-        TileType::Unused => 0.5,
-    };
-
-    let outer: f32 = match other_node.data {
-        TileType::Floor | TileType::Wall => 99.0,
-        TileType::Hallway => 10.0,
-        TileType::RoomExit => 1.0,
-        TileType::Unused => 0.5,
-    };
-
-    inner.mul_add(multiplier, outer)
+/// which `TILE_SIZE` cell of the dungeon `position` is inside
+pub fn world_cell(position: Vec2) -> IVec2 {
+    (position / TILE_SIZE).floor().as_ivec2()
 }
 
-/// Function to check if two positions are adjacent
-pub const fn is_adjacent(pos1: UVec2, pos2: UVec2) -> bool {
-    let row_diff = (pos1.x as i32 - pos2.x as i32).abs();
-    let col_diff = (pos1.y as i32 - pos2.y as i32).abs();
+/// connects each node too the nodes directly next too it
+pub fn connect_adjacent_nodes(tilegraph: &mut TileGraph) {
+    let graph: &TileGraph = tilegraph;
+    // only looking forward on each axis so every pair is connected once
+    let edges: Vec<(NodeIndex, NodeIndex, TileGraphEdge)> = graph
+        .node_references()
+        .flat_map(|(idx, node)| {
+            [UVec2::X, UVec2::Y]
+                .into_iter()
+                .filter_map(move |offset| graph.get_node_at_coord(node.tile + offset))
+                .map(move |other| (idx, other, TileGraphEdge { cost: 1.0 }))
+        })
+        .collect();
 
-    // Nodes are adjacent if their row or column difference is less than or equal to 1
-    (row_diff == 1 && col_diff == 0) || (row_diff == 0 && col_diff == 1) //|| (row_diff == 1 && col_diff == 1)
+    info!("extending graph with edges");
+    tilegraph.extend_with_edges(edges);
 }
 
-#[allow(clippy::type_complexity)]
-/// gets tilemap size in tiles
-pub fn actual_map_tile_size(
-    _settings: &DungeonSettings,
-    tile_query: &Query<
-        (
-            &GlobalTransform,
-            Option<&Collider>,
-            Option<&RoomExitTile>,
-            Option<&RoomBoundryTile>,
-        ),
-        With<GridCoords>,
-    >,
-) -> (TilemapSize, Vec2) {
-    let (max_x, max_y, min_x, min_y) = calculate_tile_extents(tile_query);
+/// gets tilemap size in tiles and center of tilemap from the area covered by `rooms`,
+/// area is grown by `TILEGRAPH_MARGIN_TILES` on every side
+pub fn actual_map_tile_size(rooms: &[(&RoomPreset, &RoomBlueprint)]) -> (TilemapSize, Vec2) {
+    let (max_x, max_y, min_x, min_y) = calculate_tile_extents(rooms);
 
     let max_tile_pos = Vec2 { x: max_x, y: max_y };
     let min_tile_pos = Vec2 { x: min_x, y: min_y };
 
-    let expanded_rect = Rect::from_corners(max_tile_pos, min_tile_pos);
+    let expanded_rect =
+        Rect::from_corners(max_tile_pos, min_tile_pos).inflate(TILEGRAPH_MARGIN_TILES * TILE_SIZE);
 
     let y_size = (expanded_rect.max.y - expanded_rect.min.y).abs();
     let x_size = (expanded_rect.max.x - expanded_rect.min.x).abs();
@@ -296,41 +134,24 @@ pub fn actual_map_tile_size(
     )
 }
 
-/// finds furthest 4 corners of tile map
-#[allow(clippy::type_complexity)]
-pub fn calculate_tile_extents(
-    tile_query: &Query<
-        (
-            &GlobalTransform,
-            Option<&Collider>,
-            Option<&RoomExitTile>,
-            Option<&RoomBoundryTile>,
-        ),
-        With<GridCoords>,
-    >,
-) -> (f32, f32, f32, f32) {
-    // Use map to extract the Vec2 translations from the iterator
-    let translations: Vec<Vec2> = tile_query
-        .iter()
-        .filter(|f| f.3.is_some())
-        // turn tile query into positions
-        .map(|f| f.0.translation().truncate())
-        // map data too translations
-        .collect();
+/// finds the centers of the furthest tiles covered by `rooms`
+pub fn calculate_tile_extents(rooms: &[(&RoomPreset, &RoomBlueprint)]) -> (f32, f32, f32, f32) {
+    let half_tile = TILE_SIZE / 2.0;
 
     // Find the maximum and minimum x and y values using iterators
-    let (max_x, max_y) = translations
+    let (max_x, max_y) = rooms
         .iter()
+        .map(|(_, room)| room.room_space.max.as_vec2() - half_tile)
         .fold((f32::NEG_INFINITY, f32::NEG_INFINITY), |acc, t| {
             (acc.0.max(t.x), acc.1.max(t.y))
         });
-    let (min_x, min_y) = translations
+    let (min_x, min_y) = rooms
         .iter()
+        .map(|(_, room)| room.room_space.min.as_vec2() + half_tile)
         .fold((f32::INFINITY, f32::INFINITY), |acc, t| {
             (acc.0.min(t.x), acc.1.min(t.y))
         });
 
-    // (ensure_tile_pos(max_x), ensure_tile_pos(max_y), ensure_tile_pos(min_x), ensure_tile_pos(min_y))
     (max_x, max_y, min_x, min_y)
 }
 
@@ -409,20 +230,63 @@ impl TileGraph {
         settings: &DungeonSettings,
         position: IVec2,
     ) -> Option<NodeIndex> {
-        self.node_references()
-            .find(|f| {
-                let node_pos = self.get_tiles_translation_world(settings, f.1.tile);
+        let origin = self.get_tiles_translation_world(settings, UVec2::ZERO);
+        // positions are truncated tile translations so rounding finds the tile they came from
+        let coords = ((position.as_vec2() - origin) / TILE_SIZE).round();
+        if coords.cmplt(Vec2::ZERO).any() {
+            return None;
+        }
+
+        let node = self.get_node_at_coord(coords.as_uvec2())?;
+        let node_pos = self.get_tiles_translation_world(settings, self[node].tile);
+        (node_pos.as_ivec2() == position).then_some(node)
+    }
+
+    /// tile type at `coords`, tiles that are not in the graph or a room are `TileType::Unused`
+    pub fn tile_type(&self, coords: UVec2) -> TileType {
+        self.get_node_at_coord(coords).map_or_else(
+            || {
+                self.room_tiles
+                    .get(&coords)
+                    .copied()
+                    .unwrap_or(TileType::Unused)
+            },
+            |f| self[f].data,
+        )
+    }
 
-                node_pos.as_ivec2() == position
-            })
-            .map(|f| f.0)
+    /// changes tile type at `coords`, coords without a tile are skipped
+    pub fn set_tile_type(&mut self, coords: UVec2, tile: TileType) {
+        if let Some(node) = self.get_node_at_coord(coords) {
+            self[node].data = tile;
+        } else if let Some(room_tile) = self.room_tiles.get_mut(&coords) {
+            *room_tile = tile;
+        }
+    }
+
+    /// tile coordinate of `world_cell` relative too the dungeon origin,
+    /// `None` if the cell is outside the tilegraph
+    pub fn get_cell_tile_coords(&self, settings: &DungeonSettings, cell: IVec2) -> Option<UVec2> {
+        let offset = cell - world_cell(self.get_tiles_translation_world(settings, UVec2::ZERO));
+        let size = UVec2 {
+            x: settings.size.x,
+            y: settings.size.y,
+        };
+
+        (offset.cmpge(IVec2::ZERO).all() && offset.as_uvec2().cmple(size).all())
+            .then(|| offset.as_uvec2())
     }
 
     /// finds `node_index` for given tile coord
     pub fn get_node_at_coord(&self, coords: UVec2) -> Option<NodeIndex> {
-        self.node_references()
-            .find(|f| f.1.tile == coords)
-            .map(|f| f.0)
+        self.node_lookup.get(&coords).copied()
+    }
+
+    /// adds `node` too graph and remembers its coordinate
+    pub fn add_tile(&mut self, node: TileGraphNode) -> NodeIndex {
+        let idx = self.graph.add_node(node);
+        self.node_lookup.insert(node.tile, idx);
+        idx
     }
 }
 
@@ -471,65 +335,3 @@ impl TileType {
         matches!(self, Self::Hallway | Self::RoomExit | Self::Unused)
     }
 }
-
-/// outputs dungeon graph into dot file
-pub fn output_graph_dot(
-    graph: &Graph<TileGraphNode, TileGraphEdge, petgraph::prelude::Undirected, u32>,
-) {
-    let graph = petgraph::dot::Dot::with_config(graph, &[petgraph::dot::Config::EdgeNoLabel]);
-    let graph = format!("{graph:?}");
-
-    if let Err(e) = fs::write("pathmap.dot", graph) {
-        warn!("error saving dot file: {}", e);
-    }
-}
-
-/// turns dungeon graph into image
-pub fn output_graph_image(
-    graph: &Graph<TileGraphNode, TileGraphEdge, petgraph::prelude::Undirected, u32>,
-    size: TilemapSize,
-) {
-    let black = Rgba([0, 0, 0, 255]);
-    let orange = Rgba([255, 100, 0, 255]);
-    let red = Rgba([255, 0, 0, 255]);
-    let blue = Rgba([17, 17, 116, 255]);
-    let white = Rgba([255, 255, 255, 255]);
-    let yellow = Rgba([227, 242, 0, 255]);
-    let green = Rgba([91, 242, 0, 255]);
-
-    let x_size = if size.x % 2 == 0 { size.x } else { size.x + 1 };
-    let y_size = if size.y % 2 == 0 { size.y } else { size.y + 1 };
-    let mut image: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(x_size + 10, y_size + 10);
-
-    for pixel in image.pixels_mut() {
-        *pixel = orange;
-    }
-
-    let nodes = graph.node_references();
-
-    for (_idx, node) in nodes {
-        let UVec2 { x, y } = node.tile;
-        let x = x + 5;
-        let y = (y_size + 5) - y;
-
-        match node.data {
-            TileType::Unused => image.get_pixel_mut(x, y).clone_from(&white),
-            TileType::Hallway => image.get_pixel_mut(x, y).clone_from(&yellow),
-            TileType::Floor => image.get_pixel_mut(x, y).clone_from(&blue),
-            TileType::Wall => image.get_pixel_mut(x, y).clone_from(&green),
-            TileType::RoomExit => image.get_pixel_mut(x, y).clone_from(&red),
-        }
-    }
-
-    let corners = [(x_size, y_size), (x_size, 0), (0, y_size), (0, 0)];
-
-    for corner in corners {
-        image
-            .get_pixel_mut(corner.0 + 5, corner.1 + 5)
-            .clone_from(&black);
-    }
-
-    if let Err(e) = image.save("pathmap.png") {
-        warn!("error saving image: {}", e);
-    }
-}
@@ -26,6 +26,7 @@ use crate::{
             components::HeroLocation,
             dungeonator_v2::GeneratorState,
             hideout::systems::{spawn_hideout, teleporter_collisions},
+            DungeonRunFinishedEvent,
         },
         items::weapons::components::AttackDamage,
     },
@@ -57,6 +58,8 @@ impl Plugin for HideOutPlugin {
             (
                 // TODO: fix scheduling
                 teleporter_collisions.run_if(on_event::<CollisionStarted>),
+                // player finished the dungeon run, send them home
+                spawn_hideout.run_if(on_event::<DungeonRunFinishedEvent>),
                 create_playable_heroes
                     .run_if(in_state(AppStage::Running).and(on_event::<LevelEvent>)),
             ),
@@ -5,24 +5,32 @@ use bevy_ecs_ldtk::{
 };
 
 use leafwing_input_manager::action_state::ActionState;
-use rand::prelude::{Rng, ThreadRng};
+use rand::prelude::Rng;
 
 use crate::{
     consts::{ACTOR_Z_INDEX, TILE_SIZE},
     game::{
+        attributes_stats::ProjectileStats,
         characters::{
             components::{CharacterMoveState, CharacterType, TeleportStatus},
             player::PlayerSelectedHero,
         },
         game_world::{
             self,
-            collisions::handle_and_removed_collider_tag,
+            collisions::{
+                handle_and_removed_collider_tag, handle_and_removed_hazard_tag,
+                merge_tile_colliders, track_hazard_contacts,
+            },
             components::{
-                ActorTeleportEvent, CharacterSpawner, HeroLocation, PlayerStartLocation,
-                RoomBoundryTile, RoomExitTile, SpawnerTimer, SpawnerWave, Teleporter,
-                TpTriggerEffect, WeaponSpawner,
+                ActorTeleportEvent, CharacterSpawner, DormantSpawner, HazardTile, HeroLocation,
+                PlayerStartLocation, RoomBoundryTile, RoomExitTile, SpawnerTimer, SpawnerWave,
+                Teleporter, TpTriggerEffect, WeaponSpawner,
+            },
+            dungeonator_v2::{
+                components::Dungeon,
+                floors::{ActiveDungeon, DungeonRun},
+                GeneratorState,
             },
-            dungeonator_v2::{components::Dungeon, GeneratorState},
             world_objects::{
                 all_levels_transformed, LdtkCharacterSpawner, LdtkHeroLocation, LdtkSpawnerWave,
                 LdtkStartLocation, LdtkTeleporter, LdtkWeaponSpawner,
@@ -63,13 +71,16 @@ impl Plugin for GameWorldPlugin {
                 SpawnerTimer,
                 Teleporter,
                 CharacterSpawner,
+                DormantSpawner,
                 WeaponSpawner,
                 PlayerStartLocation,
-                HeroLocation
+                HeroLocation,
+                HazardTile
             ]
         );
 
         app.add_event::<RegenerateDungeonEvent>()
+            .add_event::<DungeonRunFinishedEvent>()
             .add_event::<ActorTeleportEvent>()
             .add_plugins((
                 hideout::HideOutPlugin,
@@ -85,6 +96,8 @@ impl Plugin for GameWorldPlugin {
                 Update,
                 (
                     process_tile_enum_tags.run_if(any_with_component::<TileEnumTags>),
+                    merge_tile_colliders.after(process_tile_enum_tags),
+                    track_hazard_contacts,
                     handle_teleport_events.run_if(on_event::<ActorTeleportEvent>),
                     (
                         listen_rebuild_dungeon_request.run_if(
@@ -105,18 +118,22 @@ impl Plugin for GameWorldPlugin {
                 ),
             )
             .add_systems(
-                OnExit(GeneratorState::FinalizeHallways),
+                OnEnter(GeneratorState::FinishedDungeonGen),
                 populate_start_room,
             );
     }
 }
 
-/// listens for dungeon rebuild request if dungeon is finished spawning
+/// listens for dungeon rebuild request if dungeon is finished spawning.
+/// entering the floor portal moves the run too the next floor, or finishes it after the last floor
 #[allow(clippy::type_complexity)]
 fn listen_rebuild_dungeon_request(
     mut regen_events: EventReader<RegenerateDungeonEvent>,
+    mut run_finished: EventWriter<DungeonRunFinishedEvent>,
     mut cmds: Commands,
-    _generator_state: Res<State<GeneratorState>>,
+    mut run: ResMut<DungeonRun>,
+    dungeon_root: Query<(Entity, Has<ActiveDungeon>), With<Dungeon>>,
+    projectiles: Query<Entity, With<ProjectileStats>>,
     actors: Query<
         Entity,
         (
@@ -127,14 +144,47 @@ fn listen_rebuild_dungeon_request(
     >,
 ) {
     if let Some(regen_event) = regen_events.read().next() {
-        if regen_event.reason == RegenReason::FirstGeneration {
-            warn!("laying out first dungeon");
-            cmds.insert_resource(NextState::Pending(GeneratorState::LayoutDungeon));
-            return;
+        match regen_event.reason {
+            RegenReason::FirstGeneration => {
+                warn!("laying out first dungeon");
+                // floors generated ahead of time belong too the old run
+                dungeon_root
+                    .iter()
+                    .for_each(|(f, _)| cmds.entity(f).despawn_recursive());
+                *run = DungeonRun::default();
+                cmds.insert_resource(NextState::Pending(GeneratorState::LayoutDungeon));
+                return;
+            }
+            RegenReason::BossDefeat if run.on_last_floor() => {
+                info!("final floor cleared, finishing dungeon run");
+                actors
+                    .iter()
+                    .chain(projectiles.iter())
+                    .chain(dungeon_root.iter().map(|(f, _)| f))
+                    .for_each(|f| cmds.entity(f).despawn_recursive());
+
+                run_finished.send(DungeonRunFinishedEvent {
+                    floors_cleared: run.floor + 1,
+                });
+                *run = DungeonRun::default();
+                cmds.insert_resource(NextState::Pending(GeneratorState::NoDungeon));
+                regen_events.clear();
+                return;
+            }
+            RegenReason::BossDefeat => {
+                run.floor += 1;
+                info!("floor cleared, moving too floor {}", run.floor + 1);
+                // finished floor is removed now, floors generated ahead of time are kept
+                dungeon_root
+                    .iter()
+                    .filter(|(_, is_active)| *is_active)
+                    .for_each(|(f, _)| cmds.entity(f).despawn_recursive());
+            }
+            RegenReason::ManualRegen | RegenReason::PlayerDeath => {}
         }
 
         info!("despawning old actors");
-        actors.iter().for_each(|f| {
+        actors.iter().chain(projectiles.iter()).for_each(|f| {
             cmds.entity(f).despawn_recursive();
         });
 
@@ -162,10 +212,17 @@ pub struct RegenerateDungeonEvent {
     pub reason: RegenReason,
 }
 
+/// event sent when the last floor of a dungeon run is cleared
+#[derive(Event, Debug)]
+pub struct DungeonRunFinishedEvent {
+    /// how many floors the finished run had
+    pub floors_cleared: usize,
+}
+
 /// why should dungeon be rebuilt
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RegenReason {
-    /// regen dungeon because player has defeated dungeon
+    /// regen dungeon because player has defeated dungeon and entered the floor portal
     BossDefeat,
     /// player has requested a dungeon reset
     ManualRegen,
@@ -294,7 +351,7 @@ fn handle_teleport_events(
 /// spawns items in the dungeon start room for the player too use
 fn populate_start_room(
     mut ew: EventWriter<EventSpawnItem>,
-    dungeon_root: Query<Entity, With<Dungeon>>,
+    dungeon_root: Query<Entity, With<ActiveDungeon>>,
 ) {
     let Ok(dungeon) = dungeon_root.get_single() else {
         error!("no dungeon too spawn starting weaoins at");
@@ -339,6 +396,9 @@ fn process_tile_enum_tags(
             if handle_and_removed_collider_tag(&tag, &mut commands, entity, &mut tile_enum_tag) {
                 continue;
             }
+            if handle_and_removed_hazard_tag(&tag, &mut commands, entity, &mut tile_enum_tag) {
+                continue;
+            }
             if handle_and_removed_misc_tag(&tag, &mut commands, entity, &mut tile_enum_tag) {
                 continue;
             }
@@ -376,8 +436,7 @@ fn handle_and_removed_misc_tag(
 }
 
 /// returns a point inside the rect with -`inset`. `inset` is multiplied by `TILE_SIZE`
-fn random_point_inside(rect: &Rect, inset: f32) -> Option<Vec2> {
-    let mut rng = ThreadRng::default();
+fn random_point_inside(rect: &Rect, inset: f32, rng: &mut impl Rng) -> Option<Vec2> {
     let useable_space = rect.inflate(-(TILE_SIZE * inset));
     let Rect {
         min: usable_min,
@@ -5,7 +5,7 @@ use crate::{
     consts::CHARACTER_SPAWNERS_DISABLED,
     game::{
         characters::{components::CharacterType, EventSpawnCharacter},
-        game_world::components::{CharacterSpawner, SpawnerTimer, SpawnerWave},
+        game_world::components::{CharacterSpawner, DormantSpawner, SpawnerTimer, SpawnerWave},
     },
     loading::registry::ActorRegistry,
 };
@@ -17,13 +17,16 @@ pub fn character_spawners_system(
     mut cmds: Commands,
     time: Res<Time>,
     mut event_writer: EventWriter<EventSpawnCharacter>,
-    mut spawner_query: Query<(
-        Entity,
-        &GlobalTransform,
-        &mut CharacterSpawner,
-        &mut SpawnerTimer,
-        &Parent,
-    )>,
+    mut spawner_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut CharacterSpawner,
+            &mut SpawnerTimer,
+            &Parent,
+        ),
+        Without<DormantSpawner>,
+    >,
     spawner_waves: Query<(Entity, &EntityIid, &SpawnerWave)>,
     all_characters: Query<(&Transform, &CharacterType)>,
     actor_registry: Res<ActorRegistry>,
@@ -21,6 +21,7 @@ pub fn build_kbm_map(input_map: &mut InputMap<Gameplay>) {
         (Gameplay::DebugF3, KeyCode::F3),
         (Gameplay::Melee, KeyCode::KeyF),
         (Gameplay::Heal, KeyCode::KeyC),
+        (Gameplay::ToggleMap, KeyCode::KeyM),
     ]);
     input_map.insert_dual_axis(Gameplay::Move, VirtualDPad::wasd());
 }
@@ -39,6 +40,7 @@ pub fn build_gamepad_map(input_map: &mut InputMap<Gameplay>) {
         (Gameplay::Pause, GamepadButton::Start),
         (Gameplay::Melee, GamepadButton::DPadLeft),
         (Gameplay::Heal, GamepadButton::DPadRight),
+        (Gameplay::ToggleMap, GamepadButton::Select),
     ]);
 }
 
@@ -96,6 +98,8 @@ pub enum Gameplay {
     Melee,
     /// Q for keyboard
     Heal,
+    /// M for keyboard, expands minimap too full screen map
+    ToggleMap,
     /// spawn skeleton near player
     DebugF1,
     /// regenerate dungeon
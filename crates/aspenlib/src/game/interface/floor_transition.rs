@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+
+use crate::{
+    colors,
+    game::{
+        characters::player::PlayerSelectedHero,
+        game_world::dungeonator_v2::{floors::DungeonRun, GeneratorState},
+        interface::InterfaceRootTag,
+        progress::{
+            floor_portal::{FloorPortal, FloorTransition, PortalState, PORTAL_INTERACT_RANGE},
+            CurrentRunInformation,
+        },
+    },
+};
+
+/// shows floor summary near floor portals and fades screen out when one is entered
+pub struct FloorTransitionPlugin;
+
+impl Plugin for FloorTransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (
+                    spawn_floor_summary.run_if(any_with_component::<FloorPortal>),
+                    update_floor_summary,
+                )
+                    .chain(),
+                spawn_floor_fade.run_if(resource_added::<FloorTransition>),
+                update_floor_fade.run_if(resource_exists::<FloorTransition>),
+            ),
+        )
+        .add_systems(OnEnter(GeneratorState::LayoutDungeon), despawn_floor_fade)
+        .add_systems(OnEnter(GeneratorState::NoDungeon), despawn_floor_fade);
+    }
+}
+
+/// marker component for floor summary panel
+#[derive(Debug, Component)]
+pub struct FloorSummaryTag;
+
+/// text showing run stats and portal instructions
+#[derive(Debug, Component)]
+pub struct FloorSummaryText;
+
+/// marker component for floor fade overlay
+#[derive(Debug, Component)]
+pub struct FloorFadeTag;
+
+/// shows floor summary while player is near a floor portal that has not been entered
+fn update_floor_summary(
+    run: Res<DungeonRun>,
+    run_info: Res<CurrentRunInformation>,
+    player_query: Query<&GlobalTransform, With<PlayerSelectedHero>>,
+    portals: Query<(&GlobalTransform, &FloorPortal)>,
+    mut summaries: Query<&mut Node, With<FloorSummaryTag>>,
+    mut summary_text: Query<&mut Text, With<FloorSummaryText>>,
+) {
+    let near_portal = player_query.get_single().ok().and_then(|player| {
+        portals.iter().find(|(portal_transform, portal)| {
+            portal.state != PortalState::Entered
+                && portal_transform
+                    .translation()
+                    .truncate()
+                    .distance(player.translation().truncate())
+                    <= PORTAL_INTERACT_RANGE
+        })
+    });
+
+    let Some((_, portal)) = near_portal else {
+        for mut summary in &mut summaries {
+            summary.display = Display::None;
+        }
+        return;
+    };
+
+    let text = floor_summary(&run, &run_info, portal);
+    for mut summary in &mut summaries {
+        summary.display = Display::Flex;
+    }
+    for mut summary_text in &mut summary_text {
+        if summary_text.0 != text {
+            summary_text.0.clone_from(&text);
+        }
+    }
+}
+
+/// summary text for the cleared floor and what the player can do with `portal`
+fn floor_summary(
+    run: &DungeonRun,
+    run_info: &CurrentRunInformation,
+    portal: &FloorPortal,
+) -> String {
+    let next_step = if portal.state == PortalState::Unclaimed {
+        format!("press interact too claim {} coins", portal.reward)
+    } else if run.on_last_floor() {
+        "reward claimed, step into the portal too leave the dungeon".to_string()
+    } else {
+        "reward claimed, step into the portal too descend".to_string()
+    };
+
+    format!(
+        "rooms discovered: {}\nenemies defeated: {}\ndamage dealt: {:.0}\ndamage taken: {:.0}\n\n{next_step}",
+        run_info.rooms_discovered,
+        run_info.enemies_deaths,
+        run_info.enemy_physical_damage_taken,
+        run_info.player_physical_damage_taken,
+    )
+}
+
+/// spawns hidden floor summary panel under interface root if it does not exist yet
+fn spawn_floor_summary(
+    mut cmds: Commands,
+    interface_root: Query<Entity, With<InterfaceRootTag>>,
+    summaries: Query<(), With<FloorSummaryTag>>,
+) {
+    if !summaries.is_empty() {
+        return;
+    }
+    let Ok(interface_root) = interface_root.get_single() else {
+        warn!("no interface root too add floor summary too");
+        return;
+    };
+
+    cmds.entity(interface_root).with_children(|children| {
+        children
+            .spawn((
+                Name::new("FloorSummary"),
+                FloorSummaryTag,
+                BackgroundColor(colors::BLACK.with_alpha(0.8).into()),
+                Outline {
+                    width: Val::Px(3.0),
+                    offset: Val::default(),
+                    color: colors::MEDIUM_PURPLE.into(),
+                },
+                Node {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(35.0),
+                    right: Val::Percent(35.0),
+                    bottom: Val::Percent(10.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(12.0)),
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+            ))
+            .with_children(|summary_parts| {
+                summary_parts.spawn((
+                    Name::new("FloorSummaryTitle"),
+                    Text::new("Floor Cleared"),
+                    TextFont::from_font_size(28.0),
+                ));
+                summary_parts.spawn((
+                    Name::new("FloorSummaryText"),
+                    FloorSummaryText,
+                    Text::default(),
+                    TextFont::from_font_size(16.0),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                ));
+            });
+    });
+}
+
+/// spawns transparent fade overlay under interface root
+fn spawn_floor_fade(
+    mut cmds: Commands,
+    interface_root: Query<Entity, With<InterfaceRootTag>>,
+    old_fades: Query<Entity, With<FloorFadeTag>>,
+) {
+    for fade in &old_fades {
+        cmds.entity(fade).despawn_recursive();
+    }
+    let Ok(interface_root) = interface_root.get_single() else {
+        warn!("no interface root too add floor fade too");
+        return;
+    };
+
+    cmds.entity(interface_root).with_children(|children| {
+        children.spawn((
+            Name::new("FloorFade"),
+            FloorFadeTag,
+            BackgroundColor(colors::BLACK.with_alpha(0.0).into()),
+            GlobalZIndex(10),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+        ));
+    });
+}
+
+/// darkens fade overlay as the floor transition runs
+fn update_floor_fade(
+    transition: Res<FloorTransition>,
+    mut fades: Query<&mut BackgroundColor, With<FloorFadeTag>>,
+) {
+    for mut fade in &mut fades {
+        fade.0 = colors::BLACK.with_alpha(transition.fade.fraction()).into();
+    }
+}
+
+/// removes fade overlay once the next floor starts generating or the run ends
+fn despawn_floor_fade(mut cmds: Commands, fades: Query<Entity, With<FloorFadeTag>>) {
+    for fade in &fades {
+        cmds.entity(fade).despawn_recursive();
+    }
+}
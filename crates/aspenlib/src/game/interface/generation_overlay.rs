@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::{
+    colors,
+    game::{
+        game_world::dungeonator_v2::{
+            components::Dungeon,
+            floors::{ActiveDungeon, DungeonRun},
+            generation::{DungeonGenerationProgress, GenerationStage},
+            GeneratorState,
+        },
+        interface::InterfaceRootTag,
+    },
+};
+
+/// covers the screen with a progress bar while a dungeon is being generated
+pub struct GenerationOverlayPlugin;
+
+impl Plugin for GenerationOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GeneratorState::LayoutDungeon),
+            spawn_generation_overlay,
+        )
+        .add_systems(
+            Update,
+            update_generation_overlay.run_if(on_event::<DungeonGenerationProgress>),
+        )
+        .add_systems(
+            OnEnter(GeneratorState::FinishedDungeonGen),
+            despawn_generation_overlay,
+        )
+        .add_systems(
+            OnEnter(GeneratorState::NoDungeon),
+            despawn_generation_overlay,
+        );
+    }
+}
+
+/// marker component for generation overlay container
+#[derive(Debug, Component)]
+pub struct GenerationOverlayTag;
+
+/// text showing current `GenerationStage`
+#[derive(Debug, Component)]
+pub struct GenerationStageText;
+
+/// bar filled too `GenerationStage::fraction`
+#[derive(Debug, Component)]
+pub struct GenerationProgressBar;
+
+/// spawns generation overlay under interface root
+fn spawn_generation_overlay(
+    mut cmds: Commands,
+    run: Res<DungeonRun>,
+    interface_root: Query<Entity, With<InterfaceRootTag>>,
+    old_overlays: Query<Entity, With<GenerationOverlayTag>>,
+) {
+    for overlay in &old_overlays {
+        cmds.entity(overlay).despawn_recursive();
+    }
+    let Ok(interface_root) = interface_root.get_single() else {
+        warn!("no interface root too add generation overlay too");
+        return;
+    };
+
+    cmds.entity(interface_root).with_children(|children| {
+        children
+            .spawn((
+                Name::new("GenerationOverlay"),
+                GenerationOverlayTag,
+                BackgroundColor(colors::BLACK.with_alpha(0.9).into()),
+                GlobalZIndex(10),
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+            ))
+            .with_children(|overlay_parts| {
+                overlay_parts.spawn((
+                    Name::new("GenerationTitle"),
+                    Text::new(format!("Entering Floor {}", run.floor + 1)),
+                    TextFont::from_font_size(36.0),
+                ));
+                overlay_parts.spawn((
+                    Name::new("GenerationStageText"),
+                    GenerationStageText,
+                    Text::new(GenerationStage::Queued.to_string()),
+                    TextFont::from_font_size(18.0),
+                ));
+                overlay_parts
+                    .spawn((
+                        Name::new("GenerationBarContainer"),
+                        BackgroundColor(colors::DARK_SLATE_GRAY.into()),
+                        Outline {
+                            width: Val::Px(3.0),
+                            offset: Val::default(),
+                            color: colors::BLACK.into(),
+                        },
+                        Node {
+                            width: Val::Percent(40.0),
+                            height: Val::Px(20.0),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|bar_parts| {
+                        bar_parts.spawn((
+                            Name::new("GenerationProgressBar"),
+                            GenerationProgressBar,
+                            BackgroundColor(colors::LIME_GREEN.into()),
+                            Node {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                        ));
+                    });
+            });
+    });
+}
+
+/// updates overlay text and bar from latest `DungeonGenerationProgress` of the floor being entered.
+/// floors generated ahead of time are ignored
+fn update_generation_overlay(
+    run: Res<DungeonRun>,
+    mut progress_events: EventReader<DungeonGenerationProgress>,
+    dungeons: Query<&Dungeon, Without<ActiveDungeon>>,
+    mut stage_text: Query<&mut Text, With<GenerationStageText>>,
+    mut progress_bar: Query<&mut Node, With<GenerationProgressBar>>,
+) {
+    let Some(progress) = progress_events
+        .read()
+        .filter(|f| {
+            dungeons
+                .get(f.dungeon)
+                .is_ok_and(|dungeon| dungeon.settings.floor == run.floor)
+        })
+        .last()
+    else {
+        return;
+    };
+
+    for mut text in &mut stage_text {
+        text.0 = progress.stage.to_string();
+    }
+    for mut bar in &mut progress_bar {
+        bar.width = Val::Percent(progress.stage.fraction() * 100.0);
+    }
+}
+
+/// removes generation overlay
+fn despawn_generation_overlay(
+    mut cmds: Commands,
+    overlays: Query<Entity, With<GenerationOverlayTag>>,
+) {
+    for overlay in &overlays {
+        cmds.entity(overlay).despawn_recursive();
+    }
+}
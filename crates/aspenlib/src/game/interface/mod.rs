@@ -3,6 +3,10 @@ use rand::Rng;
 
 use crate::AppStage;
 
+/// floor summary and fade for floor portals
+pub mod floor_transition;
+/// dungeon generation progress overlay
+pub mod generation_overlay;
 /// pause menu module
 pub mod pause_menu;
 /// playing game ui
@@ -35,6 +39,10 @@ impl Plugin for InterfacePlugin {
             settings_menu::SettingsMenuPlugin,
             //playing ui
             playing_ui::PlayingUiPlugin,
+            // dungeon loading
+            generation_overlay::GenerationOverlayPlugin,
+            // floor portal summary and fade
+            floor_transition::FloorTransitionPlugin,
         ));
         app.add_systems(OnExit(AppStage::Loading), spawn_interface_root);
         app.add_systems(Update, update_button_color);
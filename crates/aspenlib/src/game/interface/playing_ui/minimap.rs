@@ -1,5 +1,349 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::HashMap,
+};
+use leafwing_input_manager::prelude::ActionState;
 
+use crate::{
+    colors,
+    game::{
+        characters::player::PlayerSelectedHero,
+        game_world::dungeonator_v2::{
+            components::{Dungeon, RoomBlueprint, RoomType},
+            floors::ActiveDungeon,
+            hallways::HallWayBlueprint,
+            tile_graph::{r#impl::world_cell, TileType},
+        },
+        input::action_maps::Gameplay,
+        progress::{
+            discovery::{tile_revealed, DungeonDiscovery},
+            ProgressManager,
+        },
+    },
+};
 
-// create minimap by updating a texture with pixels representing individual tiles.
-// use the tilemap debug picture generation algorithm, only query tiles from the players current room.
-// fill the texture with white when
+/// minimap width/height while it is not expanded, in pixels
+const MINIMAP_SIZE_PX: f32 = 200.0;
+
+/// width/height of player marker, in pixels
+const PLAYER_MARKER_PX: f32 = 6.0;
+
+/// map of the active dungeon drawn from its tile graph,
+/// each tile is 1 pixel of the map image
+#[derive(Debug, Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct Minimap {
+    /// is the map covering the screen
+    pub expanded: bool,
+}
+
+/// image node the dungeon is drawn into
+#[derive(Debug, Component)]
+pub struct MinimapImage;
+
+/// marks player position on the map
+#[derive(Debug, Component)]
+pub struct MinimapPlayerMarker;
+
+/// dungeon and room shown on the minimap,
+/// map image is redrawn when this changes
+#[derive(Debug, Default, Resource)]
+pub struct MinimapRooms {
+    /// dungeon the map is drawn for
+    pub dungeon: Option<Entity>,
+    /// room the player is currently inside
+    pub current: Option<Entity>,
+}
+
+/// create minimap widget in top right corner
+pub fn create_minimap(playing_ui_parts: &mut ChildBuilder, images: &mut Assets<Image>) {
+    playing_ui_parts
+        .spawn((
+            Name::new("Minimap"),
+            Minimap::default(),
+            Outline {
+                width: Val::Px(3.0),
+                offset: Val::Px(0.0),
+                color: super::colors::OUTLINE,
+            },
+            BackgroundColor(super::colors::BACKDARK),
+            minimap_node(false),
+        ))
+        .with_children(|map_parts| {
+            map_parts
+                .spawn((
+                    Name::new("MinimapImage"),
+                    MinimapImage,
+                    ImageNode::new(images.add(map_image(UVec2::ONE, vec![0; 4]))),
+                    Node {
+                        height: Val::Percent(100.0),
+                        max_width: Val::Percent(100.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|image_parts| {
+                    image_parts.spawn((
+                        Name::new("MinimapPlayerMarker"),
+                        MinimapPlayerMarker,
+                        BackgroundColor(super::colors::HIGHLIGHT),
+                        Outline {
+                            width: Val::Px(1.0),
+                            offset: Val::Px(0.0),
+                            color: super::colors::OUTLINE,
+                        },
+                        Node {
+                            display: Display::None,
+                            position_type: PositionType::Absolute,
+                            width: Val::Px(PLAYER_MARKER_PX),
+                            height: Val::Px(PLAYER_MARKER_PX),
+                            // center marker on its position
+                            margin: UiRect {
+                                left: Val::Px(-PLAYER_MARKER_PX / 2.0),
+                                bottom: Val::Px(-PLAYER_MARKER_PX / 2.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+/// layout for minimap container, expanded maps cover most of the screen
+fn minimap_node(expanded: bool) -> Node {
+    let mut node = Node {
+        position_type: PositionType::Absolute,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        padding: UiRect::all(Val::Px(4.0)),
+        ..default()
+    };
+
+    if expanded {
+        node.left = Val::Percent(5.0);
+        node.right = Val::Percent(5.0);
+        node.top = Val::Percent(5.0);
+        node.bottom = Val::Percent(5.0);
+    } else {
+        node.right = Val::Px(10.0);
+        node.top = Val::Px(10.0);
+        node.width = Val::Px(MINIMAP_SIZE_PX);
+        node.height = Val::Px(MINIMAP_SIZE_PX);
+    }
+
+    node
+}
+
+/// creates map image of `size` pixels from rgba `pixels`
+fn map_image(size: UVec2, pixels: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// expands or shrinks minimap when map key is pressed
+pub fn toggle_minimap(
+    input: Res<ActionState<Gameplay>>,
+    mut minimaps: Query<(&mut Minimap, &mut Node)>,
+) {
+    if !input.just_pressed(&Gameplay::ToggleMap) {
+        return;
+    }
+
+    for (mut minimap, mut node) in &mut minimaps {
+        minimap.expanded = !minimap.expanded;
+        *node = minimap_node(minimap.expanded);
+    }
+}
+
+/// tracks active dungeon and the room player is currently inside
+pub fn track_minimap_rooms(
+    mut map_rooms: ResMut<MinimapRooms>,
+    progress_manager: Query<&ProgressManager>,
+    active_dungeon: Query<Entity, With<ActiveDungeon>>,
+) {
+    let dungeon = active_dungeon.get_single().ok();
+    let current = progress_manager
+        .get_single()
+        .ok()
+        .and_then(ProgressManager::current_room);
+
+    if map_rooms.dungeon != dungeon || map_rooms.current != current {
+        *map_rooms = MinimapRooms { dungeon, current };
+    }
+}
+
+/// rasterizes the active dungeons tile graph into the minimap image,
+/// redrawn when the player changes room or discovers part of the dungeon
+pub fn draw_minimap(
+    map_rooms: Res<MinimapRooms>,
+    mut images: ResMut<Assets<Image>>,
+    dungeons: Query<(&Dungeon, Ref<DungeonDiscovery>, &Children)>,
+    rooms: Query<&RoomBlueprint>,
+    hallways: Query<&HallWayBlueprint>,
+    mut map_images: Query<(&ImageNode, &mut Node), With<MinimapImage>>,
+) {
+    let active_dungeon = map_rooms.dungeon.and_then(|f| dungeons.get(f).ok());
+    let discovery_changed = active_dungeon
+        .as_ref()
+        .is_some_and(|(_, discovery, _)| discovery.is_changed());
+    if !map_rooms.is_changed() && !discovery_changed {
+        return;
+    }
+
+    let Ok((image_node, mut node)) = map_images.get_single_mut() else {
+        return;
+    };
+    let Some(image) = images.get_mut(&image_node.image) else {
+        return;
+    };
+    let Some((dungeon, discovery, children)) = active_dungeon else {
+        *image = map_image(UVec2::ONE, vec![0; 4]);
+        return;
+    };
+    let settings = &dungeon.settings;
+    let tile_graph = &dungeon.tile_graph;
+
+    // which room covers each room tile
+    let mut tile_rooms: HashMap<UVec2, (Entity, &RoomBlueprint)> = HashMap::new();
+    for (room_id, room) in children
+        .iter()
+        .filter_map(|f| rooms.get(*f).ok().map(|room| (*f, room)))
+    {
+        let cells = IRect::from_corners(
+            world_cell(room.room_space.min.as_vec2()),
+            world_cell(room.room_space.max.as_vec2()),
+        );
+        for x in cells.min.x..cells.max.x {
+            for y in cells.min.y..cells.max.y {
+                let Some(coords) = tile_graph.get_cell_tile_coords(settings, IVec2 { x, y }) else {
+                    continue;
+                };
+                if tile_graph.room_tiles.contains_key(&coords)
+                    || tile_graph.tile_type(coords) == TileType::RoomExit
+                {
+                    tile_rooms.insert(coords, (room_id, room));
+                }
+            }
+        }
+    }
+    let revealed =
+        discovery.revealed_hallway_tiles(children.iter().filter_map(|f| hallways.get(*f).ok()));
+
+    let size = UVec2 {
+        x: settings.size.x + 1,
+        y: settings.size.y + 1,
+    };
+    let mut pixels = vec![0; (size.x * size.y * 4) as usize];
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let coords = UVec2 { x, y };
+            let Some(color) = tile_color(
+                tile_graph.tile_type(coords),
+                tile_rooms.get(&coords).copied(),
+                tile_revealed(&revealed, coords),
+                &discovery,
+                map_rooms.current,
+            ) else {
+                continue;
+            };
+
+            // images start at the top so y is flipped
+            let pixel = (((size.y - 1 - y) * size.x + x) * 4) as usize;
+            pixels[pixel..pixel + 4].copy_from_slice(&color.to_u8_array());
+        }
+    }
+
+    *image = map_image(size, pixels);
+    node.aspect_ratio = Some(size.x as f32 / size.y as f32);
+}
+
+/// map color for `tile`, rooms are darkened until discovered and hallways are hidden until walked through.
+/// secret rooms are hidden until discovered.
+/// `None` for tiles that are not drawn
+fn tile_color(
+    tile: TileType,
+    room: Option<(Entity, &RoomBlueprint)>,
+    hallway_revealed: bool,
+    discovery: &DungeonDiscovery,
+    current_room: Option<Entity>,
+) -> Option<Srgba> {
+    if tile.is_unused() || (room.is_none() && !hallway_revealed) {
+        return None;
+    }
+    let Some((room_id, room)) = room else {
+        // hallway tiles
+        return Some(if tile.is_wall() {
+            colors::GRAY
+        } else {
+            colors::TAN
+        });
+    };
+    let discovered = discovery.room_discovered(room.id);
+    // secret rooms are not shown at all until the player finds them
+    if room.descriptor.rtype == RoomType::Secret && !discovered {
+        return None;
+    }
+
+    Some(match tile {
+        TileType::RoomExit if discovered || hallway_revealed => colors::WHEAT,
+        TileType::Wall if discovered => colors::LIGHT_GRAY,
+        TileType::Wall => colors::DIM_GRAY,
+        _ if current_room == Some(room_id) => colors::LIGHT_SKY_BLUE,
+        _ if discovered => match room.descriptor.rtype {
+            RoomType::DungeonStart => colors::SEA_GREEN,
+            RoomType::DungeonEnd => colors::CRIMSON,
+            RoomType::MiniBoss => colors::DARK_ORANGE,
+            RoomType::Special => colors::GOLDENROD,
+            RoomType::Secret => colors::ORCHID,
+            RoomType::Normal | RoomType::Hideout => colors::STEEL_BLUE,
+        },
+        _ => colors::DARK_SLATE_GRAY,
+    })
+}
+
+/// moves player marker too the tile the player is standing on
+pub fn update_minimap_marker(
+    map_rooms: Res<MinimapRooms>,
+    dungeons: Query<(&Dungeon, &GlobalTransform)>,
+    player_query: Query<&GlobalTransform, With<PlayerSelectedHero>>,
+    mut markers: Query<&mut Node, With<MinimapPlayerMarker>>,
+) {
+    let Ok(mut marker) = markers.get_single_mut() else {
+        return;
+    };
+
+    let player_tile = map_rooms
+        .dungeon
+        .and_then(|f| dungeons.get(f).ok())
+        .zip(player_query.get_single().ok())
+        .and_then(|((dungeon, dungeon_transform), player)| {
+            let position =
+                player.translation().truncate() - dungeon_transform.translation().truncate();
+            dungeon
+                .tile_graph
+                .get_cell_tile_coords(&dungeon.settings, world_cell(position))
+                .map(|coords| (coords, dungeon.settings.size))
+        });
+
+    let Some((coords, size)) = player_tile else {
+        marker.display = Display::None;
+        return;
+    };
+
+    marker.display = Display::Flex;
+    marker.left = Val::Percent((coords.x as f32 + 0.5) / (size.x + 1) as f32 * 100.0);
+    marker.bottom = Val::Percent((coords.y as f32 + 0.5) / (size.y + 1) as f32 * 100.0);
+}
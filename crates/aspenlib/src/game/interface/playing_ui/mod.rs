@@ -10,6 +10,9 @@ mod action_buttons;
 /// player weapon ui widgets
 pub mod gun_hud;
 
+/// dungeon map ui widget
+pub mod minimap;
+
 /// player vital ui widgets
 mod stat_hud;
 
@@ -18,7 +21,11 @@ pub struct PlayingUiPlugin;
 
 impl Plugin for PlayingUiPlugin {
     fn build(&self, app: &mut App) {
-        register_types!(app, [stat_hud::StatBar, gun_hud::PlayerAmmoBar]);
+        register_types!(
+            app,
+            [stat_hud::StatBar, gun_hud::PlayerAmmoBar, minimap::Minimap]
+        );
+        app.init_resource::<minimap::MinimapRooms>();
         app.add_systems(OnEnter(AppStage::Starting), spawn_playing_ui)
             .add_systems(
                 Update,
@@ -29,6 +36,12 @@ impl Plugin for PlayingUiPlugin {
                         gun_hud::update_ui_ammo_counter,
                         gun_hud::update_ui_ammo_slots,
                         gun_hud::gunhud_visibility_system,
+                        minimap::toggle_minimap,
+                        minimap::track_minimap_rooms,
+                        minimap::draw_minimap
+                            .after(minimap::track_minimap_rooms)
+                            .run_if(resource_changed::<minimap::MinimapRooms>),
+                        minimap::update_minimap_marker,
                     )
                         .run_if(playing_game()),
                 ),
@@ -74,6 +87,7 @@ fn spawn_playing_ui(
     mut cmds: Commands,
     // assets: Res<AspenInitHandles>,
     touch_assets: Res<AspenTouchHandles>,
+    mut images: ResMut<Assets<Image>>,
     interface_root: Query<Entity, With<InterfaceRootTag>>,
 ) {
     cmds.entity(interface_root.single())
@@ -97,6 +111,7 @@ fn spawn_playing_ui(
                     // TODO: update portrait based on selected player
                     gun_hud::create_gun_hud(playing_ui_parts);
                     create_hud_container(playing_ui_parts, touch_assets);
+                    minimap::create_minimap(playing_ui_parts, &mut images);
                 });
         });
 }
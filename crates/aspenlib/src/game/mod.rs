@@ -69,6 +69,7 @@ impl Plugin for AspenHallsPlugin {
             // actual game plugin
             .add_plugins((
                 progress::GameProgressPlugin,
+                attributes_stats::StatsPlugin,
                 audio::AudioPlugin,
                 combat::CombatPlugin,
                 characters::CharactersPlugin,
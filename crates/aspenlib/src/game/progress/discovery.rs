@@ -0,0 +1,162 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_tilemap::tiles::{TilePos, TileVisible};
+
+use crate::game::{
+    characters::player::PlayerSelectedHero,
+    game_world::dungeonator_v2::{
+        components::{Dungeon, RoomBlueprint, RoomID},
+        floors::ActiveDungeon,
+        hallways::{offset_tile, walls::NEIGHBOR_OFFSETS, HallWayBlueprint, HallwayLayer},
+        tile_graph::r#impl::world_cell,
+    },
+    progress::{CurrentRunInformation, ProgressManager},
+};
+
+/// rooms and hallways the player has visited in a dungeon,
+/// undiscovered parts of the dungeon are hidden
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct DungeonDiscovery {
+    /// rooms the player has entered
+    pub rooms: HashSet<RoomID>,
+    /// hallways the player has walked through, keyed by the rooms they connect
+    pub hallways: HashSet<(RoomID, RoomID)>,
+}
+
+impl DungeonDiscovery {
+    /// has the player entered `room`
+    pub fn room_discovered(&self, room: RoomID) -> bool {
+        self.rooms.contains(&room)
+    }
+
+    /// has the player walked through `hallway`
+    pub fn hallway_discovered(&self, hallway: &HallWayBlueprint) -> bool {
+        self.hallways.contains(&hallway.connected_rooms)
+    }
+
+    /// floor tiles of every discovered hallway in `hallways`
+    pub fn revealed_hallway_tiles<'a>(
+        &self,
+        hallways: impl IntoIterator<Item = &'a HallWayBlueprint>,
+    ) -> HashSet<UVec2> {
+        hallways
+            .into_iter()
+            .filter(|f| self.hallway_discovered(f))
+            .flat_map(|f| f.floor_tiles.iter().copied())
+            .collect()
+    }
+}
+
+/// is `tile` or a tile next too it in `revealed`, walls next too revealed floors are shown too
+pub fn tile_revealed(revealed: &HashSet<UVec2>, tile: UVec2) -> bool {
+    revealed.contains(&tile)
+        || NEIGHBOR_OFFSETS
+            .iter()
+            .filter_map(|offset| offset_tile(tile, *offset))
+            .any(|f| revealed.contains(&f))
+}
+
+/// starts tracking discovery for dungeons the player enters
+pub fn start_dungeon_discovery(
+    mut cmds: Commands,
+    new_dungeons: Query<Entity, (Added<ActiveDungeon>, Without<DungeonDiscovery>)>,
+) {
+    for dungeon in &new_dungeons {
+        cmds.entity(dungeon).insert(DungeonDiscovery::default());
+    }
+}
+
+/// discovers the players current room and any hallway the player is standing in
+pub fn update_dungeon_discovery(
+    progress_manager: Query<&ProgressManager>,
+    player_query: Query<&GlobalTransform, With<PlayerSelectedHero>>,
+    mut dungeons: Query<
+        (&Dungeon, &GlobalTransform, &Children, &mut DungeonDiscovery),
+        With<ActiveDungeon>,
+    >,
+    rooms: Query<&RoomBlueprint>,
+    hallways: Query<&HallWayBlueprint>,
+    mut run_info: ResMut<CurrentRunInformation>,
+) {
+    let (Ok(progress_manager), Ok(player)) =
+        (progress_manager.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+    let Ok((dungeon, dungeon_transform, children, mut discovery)) = dungeons.get_single_mut()
+    else {
+        return;
+    };
+
+    if let Some(room) = progress_manager
+        .current
+        .current_room
+        .and_then(|f| rooms.get(f).ok())
+    {
+        if !discovery.room_discovered(room.id) {
+            info!("discovered room {}", room.name);
+            discovery.rooms.insert(room.id);
+            run_info.rooms_discovered += 1;
+        }
+        return;
+    }
+
+    let position = player.translation().truncate() - dungeon_transform.translation().truncate();
+    let Some(tile) = dungeon
+        .tile_graph
+        .get_cell_tile_coords(&dungeon.settings, world_cell(position))
+    else {
+        return;
+    };
+
+    for hallway in children.iter().filter_map(|f| hallways.get(*f).ok()) {
+        if hallway.floor_tiles.contains(&tile) && !discovery.hallway_discovered(hallway) {
+            info!("discovered hallway {:?}", hallway.connected_rooms);
+            discovery.hallways.insert(hallway.connected_rooms);
+        }
+    }
+}
+
+/// hides rooms and hallway tiles the player has not discovered yet
+pub fn apply_fog_of_war(
+    dungeons: Query<(&DungeonDiscovery, &Children), Changed<DungeonDiscovery>>,
+    mut rooms: Query<(&RoomBlueprint, &mut Visibility)>,
+    hallways: Query<&HallWayBlueprint>,
+    hallway_layers: Query<&Children, With<HallwayLayer>>,
+    mut hallway_tiles: Query<(&TilePos, &mut TileVisible)>,
+) {
+    for (discovery, children) in &dungeons {
+        for child in children {
+            let Ok((room, mut visibility)) = rooms.get_mut(*child) else {
+                continue;
+            };
+            visibility.set_if_neq(if discovery.room_discovered(room.id) {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            });
+        }
+
+        let revealed =
+            discovery.revealed_hallway_tiles(children.iter().filter_map(|f| hallways.get(*f).ok()));
+        for tile in children
+            .iter()
+            .filter_map(|f| hallway_layers.get(*f).ok())
+            .flatten()
+        {
+            let Ok((position, mut visible)) = hallway_tiles.get_mut(*tile) else {
+                continue;
+            };
+            let shown = tile_revealed(
+                &revealed,
+                UVec2 {
+                    x: position.x,
+                    y: position.y,
+                },
+            );
+            if visible.0 != shown {
+                visible.0 = shown;
+            }
+        }
+    }
+}
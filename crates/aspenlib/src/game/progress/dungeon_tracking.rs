@@ -4,14 +4,12 @@ use crate::game::{
         components::CharacterType,
         player::PlayerSelectedHero,
     },
-    game_world::{
-        dungeonator_v2::{
-            components::{BossState, RoomBlueprint},
-            GeneratorState,
-        },
-        RegenReason, RegenerateDungeonEvent,
+    game_world::dungeonator_v2::{
+        components::{BossState, RoomBlueprint},
+        floors::ActiveDungeon,
+        GeneratorState,
     },
-    progress::ProgressManager,
+    progress::{floor_portal::FloorClearedEvent, ProgressManager},
 };
 use bevy::prelude::*;
 use big_brain::prelude::{HasThinker, Score};
@@ -19,7 +17,8 @@ use big_brain::prelude::{HasThinker, Score};
 /// update player current room
 pub fn update_player_current_room(
     mut progress_manager: Query<&mut ProgressManager>,
-    room_query: Query<(Entity, &GlobalTransform, &RoomBlueprint)>,
+    room_query: Query<(Entity, &GlobalTransform, &RoomBlueprint, &Parent)>,
+    active_dungeon: Query<(), With<ActiveDungeon>>,
     player_query: Query<&Transform, With<PlayerSelectedHero>>,
 ) {
     let mut progress_manager = progress_manager.single_mut();
@@ -27,22 +26,18 @@ pub fn update_player_current_room(
 
     let current_room = room_query
         .iter()
-        .find(|f| {
-            let room_xy = f.1.translation().xy();
-            let size = f.2.room_space.size();
-
-            let room_rect = Rect::from_corners(room_xy, room_xy + size.as_vec2());
-            room_rect.contains(player_position)
-        })
+        .filter(|f| active_dungeon.contains(f.3.get()))
+        .find(|f| f.2.world_rect(f.1).contains(player_position))
         .map(|f| f.0);
 
     progress_manager.current.current_room = current_room;
 }
 
-/// updates boss state based on boss ai status
+/// updates boss state based on boss ai status,
+/// defeating the boss opens the floor portal
 pub fn update_boss_state(
     mut progress_manager: Query<&mut ProgressManager>,
-    mut regen_event: EventWriter<RegenerateDungeonEvent>,
+    mut cleared_event: EventWriter<FloorClearedEvent>,
     actor_query: Query<(Entity, &Transform, &CharacterType), Without<PlayerSelectedHero>>,
     children: Query<&Children>,
     has_thinkers: Query<&HasThinker>,
@@ -73,9 +68,7 @@ pub fn update_boss_state(
         && progress_manager.current.boss_state == BossState::Defeated
     {
         progress_manager.current.boss_state = BossState::UnSpawned;
-        regen_event.send(RegenerateDungeonEvent {
-            reason: RegenReason::BossDefeat,
-        });
+        cleared_event.send(FloorClearedEvent);
         return;
     }
 
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    colors,
+    consts::{ACTOR_Z_INDEX, TILE_SIZE},
+    game::{
+        characters::player::PlayerSelectedHero,
+        game_world::{
+            dungeonator_v2::{
+                components::{BossState, RoomBlueprint, RoomType},
+                floors::{ActiveDungeon, DungeonRun},
+            },
+            RegenReason, RegenerateDungeonEvent,
+        },
+        input::action_maps::Gameplay,
+        progress::{PlayerSaveInformation, ProgressManager},
+    },
+};
+
+/// how close the player must be too a portal too see the floor summary and claim its reward
+pub const PORTAL_INTERACT_RANGE: f32 = TILE_SIZE * 3.0;
+
+/// how close the player must be too an open portal too step into it
+const PORTAL_ENTER_RANGE: f32 = TILE_SIZE * 0.75;
+
+/// coins rewarded for clearing a floor, multiplied by floor number
+const FLOOR_REWARD_COINS: i32 = 25;
+
+/// seconds the screen takes too fade out before the next floor is requested
+const FLOOR_FADE_SECONDS: f32 = 1.0;
+
+/// sent when the boss of the active dungeon is defeated
+#[derive(Debug, Event)]
+pub struct FloorClearedEvent;
+
+/// portal spawned in the dungeon end room after its boss is defeated,
+/// moves the player too the next floor once its reward is claimed
+#[derive(Debug, Component, Default, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct FloorPortal {
+    /// where the player is with this portal
+    pub state: PortalState,
+    /// coins given too the player when the reward is claimed
+    pub reward: i32,
+}
+
+/// interaction state of a `FloorPortal`
+#[derive(Debug, Default, Reflect, Clone, Copy, PartialEq, Eq)]
+pub enum PortalState {
+    /// reward has not been claimed, portal can not be entered yet
+    #[default]
+    Unclaimed,
+    /// reward was claimed, player can step into portal
+    Open,
+    /// player stepped into portal and the floor transition is running
+    Entered,
+}
+
+/// screen fade started by entering a `FloorPortal`,
+/// next floor is requested when the fade is finished
+#[derive(Debug, Resource)]
+pub struct FloorTransition {
+    /// time until screen is fully faded out
+    pub fade: Timer,
+}
+
+/// spawns a `FloorPortal` in the middle of the active dungeons end room
+pub fn spawn_floor_portal(
+    mut cmds: Commands,
+    mut cleared_events: EventReader<FloorClearedEvent>,
+    run: Res<DungeonRun>,
+    dungeons: Query<(Entity, &GlobalTransform, &Children), With<ActiveDungeon>>,
+    rooms: Query<(&GlobalTransform, &RoomBlueprint)>,
+    portals: Query<(), With<FloorPortal>>,
+    player_query: Query<&GlobalTransform, With<PlayerSelectedHero>>,
+) {
+    cleared_events.clear();
+    if !portals.is_empty() {
+        return;
+    }
+    let Ok((dungeon, dungeon_transform, children)) = dungeons.get_single() else {
+        warn!("floor was cleared without an active dungeon");
+        return;
+    };
+
+    let end_room = children
+        .iter()
+        .filter_map(|f| rooms.get(*f).ok())
+        .find(|(_, room)| room.descriptor.rtype == RoomType::DungeonEnd)
+        .map(|(room_transform, room)| room.world_rect(room_transform).center());
+    let Some(portal_position) = end_room.or_else(|| {
+        warn!("active dungeon has no end room, spawning portal at player");
+        player_query
+            .get_single()
+            .ok()
+            .map(|f| f.translation().truncate())
+    }) else {
+        return;
+    };
+
+    info!("floor {} cleared, opening floor portal", run.floor + 1);
+    // portal is parented too dungeon so it is removed with the floor
+    let local_position = portal_position - dungeon_transform.translation().truncate();
+    cmds.spawn((
+        Name::new("FloorPortal"),
+        FloorPortal {
+            state: PortalState::Unclaimed,
+            reward: FLOOR_REWARD_COINS * (run.floor as i32 + 1),
+        },
+        Sprite {
+            color: colors::MEDIUM_PURPLE.into(),
+            custom_size: Some(Vec2::splat(TILE_SIZE * 2.0)),
+            ..default()
+        },
+        Transform::from_translation(local_position.extend(ACTOR_Z_INDEX - 1.0)),
+    ))
+    .set_parent(dungeon);
+}
+
+/// gives the player the portals reward when interact is pressed near an unclaimed portal
+pub fn claim_portal_reward(
+    actions: Res<ActionState<Gameplay>>,
+    mut save_info: ResMut<PlayerSaveInformation>,
+    mut progress_manager: Query<&mut ProgressManager>,
+    player_query: Query<&GlobalTransform, With<PlayerSelectedHero>>,
+    mut portals: Query<(&GlobalTransform, &mut FloorPortal)>,
+) {
+    if !actions.just_pressed(&Gameplay::Interact) {
+        return;
+    }
+    let (Ok(mut progress_manager), Ok(player)) =
+        (progress_manager.get_single_mut(), player_query.get_single())
+    else {
+        return;
+    };
+
+    for (portal_transform, mut portal) in &mut portals {
+        let distance = portal_transform
+            .translation()
+            .truncate()
+            .distance(player.translation().truncate());
+        if portal.state != PortalState::Unclaimed || distance > PORTAL_INTERACT_RANGE {
+            continue;
+        }
+
+        info!("claimed floor reward of {} coins", portal.reward);
+        save_info.player_money += portal.reward;
+        progress_manager.overall.coin += portal.reward;
+        portal.state = PortalState::Open;
+    }
+}
+
+/// starts the floor transition when the player steps into an open portal
+pub fn enter_floor_portal(
+    mut cmds: Commands,
+    player_query: Query<&GlobalTransform, With<PlayerSelectedHero>>,
+    mut portals: Query<(&GlobalTransform, &mut FloorPortal)>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+
+    for (portal_transform, mut portal) in &mut portals {
+        let distance = portal_transform
+            .translation()
+            .truncate()
+            .distance(player.translation().truncate());
+        if portal.state != PortalState::Open || distance > PORTAL_ENTER_RANGE {
+            continue;
+        }
+
+        info!("player entered floor portal");
+        portal.state = PortalState::Entered;
+        cmds.insert_resource(FloorTransition {
+            fade: Timer::from_seconds(FLOOR_FADE_SECONDS, TimerMode::Once),
+        });
+    }
+}
+
+/// finishes the floor transition once the screen is faded out,
+/// forgets the old floor and requests the next one
+pub fn finish_floor_transition(
+    mut cmds: Commands,
+    time: Res<Time>,
+    mut transition: ResMut<FloorTransition>,
+    mut progress_manager: Query<&mut ProgressManager>,
+    mut regen_event: EventWriter<RegenerateDungeonEvent>,
+) {
+    if !transition.fade.tick(time.delta()).finished() {
+        return;
+    }
+    cmds.remove_resource::<FloorTransition>();
+
+    if let Ok(mut progress_manager) = progress_manager.get_single_mut() {
+        progress_manager.current.boss_state = BossState::UnSpawned;
+        progress_manager.current.boss_id = None;
+        progress_manager.current.current_room = None;
+    }
+    regen_event.send(RegenerateDungeonEvent {
+        reason: RegenReason::BossDefeat,
+    });
+}
@@ -1,14 +1,29 @@
 // switch too hideout if player dies inside dungeon
 // switch too hideout if player exits dungeon by choice
 // lock doors of room player is currently in until room enemies are defeated
-// if player defeats boss, regenerate dungeon and bump dungeon level
+// if player defeats boss, open a portal too the next dungeon level
 
 use crate::{
-    game::game_world::dungeonator_v2::components::BossState, register_types, AppStage, GameStage,
+    game::game_world::{
+        dungeonator_v2::{
+            components::{BossState, RoomBlueprint},
+            generation::DungeonGenerated,
+        },
+        DungeonRunFinishedEvent,
+    },
+    register_types, AppStage, GameStage,
 };
 use bevy::prelude::*;
 
+/// tracks rooms and hallways the player has found
+pub mod discovery;
 mod dungeon_tracking;
+/// portal too the next floor and the transition too it
+pub mod floor_portal;
+/// locks rooms until there enemies are defeated
+pub mod room_encounters;
+/// breakable walls hiding secret rooms and there loot
+pub mod secret_rooms;
 
 /// player progression tracking module
 pub struct GameProgressPlugin;
@@ -20,22 +35,72 @@ impl Plugin for GameProgressPlugin {
             [
                 ProgressManager,
                 CurrentRunInformation,
-                PlayerSaveInformation
+                PlayerSaveInformation,
+                room_encounters::RoomEncounter,
+                room_encounters::SealedExit,
+                discovery::DungeonDiscovery,
+                floor_portal::FloorPortal,
+                secret_rooms::BreakableWall,
+                secret_rooms::SecretRoomLooted
             ]
         );
 
         app.insert_resource(CurrentRunInformation::default())
-            .insert_resource(PlayerSaveInformation::default());
+            .insert_resource(PlayerSaveInformation::default())
+            .add_event::<floor_portal::FloorClearedEvent>();
 
         app.add_systems(OnExit(AppStage::Loading), spawn_progress_manager);
+        app.add_systems(
+            Update,
+            count_finished_runs.run_if(on_event::<DungeonRunFinishedEvent>),
+        );
         app.add_systems(
             FixedUpdate,
             (
                 dungeon_tracking::update_boss_state,
                 dungeon_tracking::update_player_current_room,
+                (
+                    room_encounters::start_room_encounters,
+                    room_encounters::finish_room_encounters,
+                    discovery::update_dungeon_discovery,
+                    secret_rooms::loot_secret_rooms,
+                )
+                    .after(dungeon_tracking::update_player_current_room),
             )
                 .run_if(in_state(GameStage::PlayingGame)),
         );
+        app.add_systems(
+            Update,
+            room_encounters::prepare_room_encounters.run_if(any_with_component::<RoomBlueprint>),
+        );
+        app.add_systems(
+            Update,
+            (
+                secret_rooms::spawn_breakable_walls.run_if(on_event::<DungeonGenerated>),
+                secret_rooms::damage_breakable_walls,
+            ),
+        );
+        app.add_systems(
+            Update,
+            (
+                discovery::start_dungeon_discovery,
+                discovery::apply_fog_of_war,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            Update,
+            (
+                floor_portal::spawn_floor_portal
+                    .run_if(on_event::<floor_portal::FloorClearedEvent>),
+                floor_portal::claim_portal_reward,
+                floor_portal::enter_floor_portal,
+                floor_portal::finish_floor_transition
+                    .run_if(resource_exists::<floor_portal::FloorTransition>),
+            )
+                .chain()
+                .run_if(in_state(GameStage::PlayingGame)),
+        );
     }
 }
 
@@ -49,6 +114,13 @@ pub struct ProgressManager {
     overall: OverallProgressState,
 }
 
+impl ProgressManager {
+    /// room entity the player is currently inside
+    pub const fn current_room(&self) -> Option<Entity> {
+        self.current.current_room
+    }
+}
+
 /// current dungeon progression for player
 #[derive(Debug, Reflect, Component, Clone)]
 pub struct CurrentDungeonState {
@@ -87,6 +159,8 @@ pub struct CurrentRunInformation {
     pub enemy_damage_sent: f32,
     /// amount of damage player have fired that hit enemy and didn't get counted
     pub player_damage_sent: f32,
+    /// rooms player has entered this run
+    pub rooms_discovered: i32,
 }
 
 //TODO: save this too file, load from file when rebooting game
@@ -128,3 +202,14 @@ fn spawn_progress_manager(mut cmds: Commands) {
         },
     ));
 }
+
+/// counts finished dungeon runs for the player save
+fn count_finished_runs(
+    mut save_info: ResMut<PlayerSaveInformation>,
+    mut run_finished: EventReader<DungeonRunFinishedEvent>,
+) {
+    for event in run_finished.read() {
+        info!("dungeon run finished after {} floors", event.floors_cleared);
+        save_info.runs_completed += 1;
+    }
+}
@@ -0,0 +1,197 @@
+use avian2d::prelude::{Collider, RigidBody};
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    colors,
+    consts::{AspenCollisionLayer, ACTOR_Z_INDEX, TILE_SIZE},
+    game::{
+        characters::{components::CharacterType, player::PlayerSelectedHero},
+        game_world::{
+            components::{CharacterSpawner, DormantSpawner},
+            dungeonator_v2::components::RoomBlueprint,
+        },
+        progress::ProgressManager,
+    },
+};
+
+/// encounter state for a dungeon room with character spawners
+#[derive(Debug, Component, Default, Reflect, Clone)]
+#[reflect(Component)]
+pub struct RoomEncounter {
+    /// where this room is in its encounter
+    pub state: EncounterState,
+    /// character spawners inside this room
+    pub spawners: Vec<Entity>,
+    /// doors blocking exits while room is sealed
+    pub doors: Vec<Entity>,
+}
+
+/// progress of a room encounter
+#[derive(Debug, Default, Reflect, Clone, Copy, PartialEq, Eq)]
+pub enum EncounterState {
+    /// player has not entered room yet, spawners are waiting
+    #[default]
+    Dormant,
+    /// player is inside room and exits are sealed
+    Sealed,
+    /// every character from the rooms spawners is dead
+    Cleared,
+}
+
+/// blocks a room exit while the rooms encounter is running
+#[derive(Debug, Component, Default, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct SealedExit;
+
+/// how many tiles the player must be inside a room before exits are sealed,
+/// keeps doors from being placed on top of player
+const SEAL_INSET_TILES: f32 = 2.0;
+
+/// puts character spawners inside dungeon rooms too sleep and adds them too the rooms `RoomEncounter`
+pub fn prepare_room_encounters(
+    mut cmds: Commands,
+    new_spawners: Query<Entity, Added<CharacterSpawner>>,
+    parents: Query<&Parent>,
+    mut rooms: Query<Option<&mut RoomEncounter>, With<RoomBlueprint>>,
+) {
+    // rooms spawn all there spawners at once, group them so each room gets 1 encounter
+    let mut room_spawners: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for spawner in &new_spawners {
+        let Some(room) = parents.iter_ancestors(spawner).find(|f| rooms.contains(*f)) else {
+            // not inside a dungeon room, hideout spawners stay awake
+            continue;
+        };
+
+        cmds.entity(spawner).insert(DormantSpawner);
+        room_spawners.entry(room).or_default().push(spawner);
+    }
+
+    for (room, spawners) in room_spawners {
+        match rooms.get_mut(room).expect("room was found in query") {
+            Some(mut encounter) => encounter.spawners.extend(spawners),
+            None => {
+                cmds.entity(room).insert(RoomEncounter {
+                    state: EncounterState::Dormant,
+                    spawners,
+                    doors: Vec::new(),
+                });
+            }
+        }
+    }
+}
+
+/// seals exits of the players current room and wakes its spawners if any still has waves left,
+/// random wave spawners count as an encounter until they have spawned `max_spawned` characters
+pub fn start_room_encounters(
+    mut cmds: Commands,
+    progress_manager: Query<&ProgressManager>,
+    player_query: Query<&Transform, With<PlayerSelectedHero>>,
+    mut rooms: Query<(
+        &GlobalTransform,
+        &RoomBlueprint,
+        &mut RoomEncounter,
+        &Parent,
+    )>,
+    spawners: Query<&CharacterSpawner>,
+) {
+    let (Ok(progress_manager), Ok(player)) =
+        (progress_manager.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+    let Some(room_id) = progress_manager.current.current_room else {
+        return;
+    };
+    let Ok((room_transform, room, mut encounter, dungeon)) = rooms.get_mut(room_id) else {
+        return;
+    };
+    if encounter.state != EncounterState::Dormant {
+        return;
+    }
+
+    let sealing_area = room
+        .world_rect(room_transform)
+        .inflate(-(TILE_SIZE * SEAL_INSET_TILES));
+    if !sealing_area.contains(player.translation.truncate()) {
+        return;
+    }
+
+    let has_waves = encounter
+        .spawners
+        .iter()
+        .filter_map(|f| spawners.get(*f).ok())
+        .any(CharacterSpawner::has_waves_left);
+    if !has_waves {
+        // nothing too fight, spawners stay asleep
+        encounter.state = EncounterState::Cleared;
+        return;
+    }
+
+    for spawner in &encounter.spawners {
+        cmds.entity(*spawner).remove::<DormantSpawner>();
+    }
+
+    info!("sealing {} exits for room {}", room.exits.len(), room.name);
+    encounter.state = EncounterState::Sealed;
+    for exit in &room.exits {
+        // exits are relative too dungeon, rooms can be turned so doors are added too dungeon
+        let door_position = exit.position.as_vec2() + Vec2::splat(TILE_SIZE / 2.0);
+
+        let door = cmds
+            .spawn((
+                Name::new("SealedExit"),
+                SealedExit,
+                Sprite {
+                    color: colors::SADDLE_BROWN.into(),
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..default()
+                },
+                Transform::from_translation(door_position.extend(ACTOR_Z_INDEX - 1.0)),
+                RigidBody::Static,
+                Collider::rectangle(TILE_SIZE, TILE_SIZE),
+                AspenCollisionLayer::static_object(),
+            ))
+            .set_parent(dungeon.get())
+            .id();
+        encounter.doors.push(door);
+    }
+}
+
+/// unseals rooms when all spawner waves are used and every spawned character is dead
+pub fn finish_room_encounters(
+    mut cmds: Commands,
+    mut rooms: Query<(&RoomBlueprint, &mut RoomEncounter)>,
+    spawners: Query<&CharacterSpawner>,
+    characters: Query<(), With<CharacterType>>,
+) {
+    for (room, mut encounter) in &mut rooms {
+        if encounter.state != EncounterState::Sealed {
+            continue;
+        }
+
+        let room_spawners: Vec<&CharacterSpawner> = encounter
+            .spawners
+            .iter()
+            .filter_map(|f| spawners.get(*f).ok())
+            .collect();
+        let waves_left = room_spawners.iter().any(|f| f.has_waves_left());
+        let characters_alive = room_spawners
+            .iter()
+            .flat_map(|f| &f.spawned_characters)
+            .any(|f| characters.contains(*f));
+
+        if waves_left || characters_alive {
+            continue;
+        }
+
+        info!("room {} cleared, unsealing exits", room.name);
+        encounter.state = EncounterState::Cleared;
+        for door in encounter.doors.drain(..) {
+            cmds.entity(door).despawn_recursive();
+        }
+        // stop random wave spawners from refilling a cleared room
+        for spawner in &encounter.spawners {
+            cmds.entity(*spawner).insert(DormantSpawner);
+        }
+    }
+}
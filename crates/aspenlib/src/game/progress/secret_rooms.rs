@@ -0,0 +1,184 @@
+use avian2d::prelude::{Collider, RigidBody};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::{
+    colors,
+    consts::{AspenCollisionLayer, ACTOR_Z_INDEX, TILE_SIZE},
+    game::{
+        attributes_stats::{Damage, DamageQueue},
+        characters::player::PlayerSelectedHero,
+        game_world::dungeonator_v2::{
+            components::{Dungeon, DungeonRng, RoomBlueprint, RoomType},
+            generation::DungeonGenerated,
+            hallways::HallWayBlueprint,
+            tile_graph::{r#impl::world_cell, TileType},
+        },
+        items::EventSpawnItem,
+        progress::ProgressManager,
+    },
+    loading::{
+        assets::AspenLevelsetHandles,
+        custom_assets::loot_definitions::SecretLootTable,
+        registry::{ActorRegistry, RegistryIdentifier},
+    },
+};
+
+/// damage a wall covering a secret room exit can take before breaking
+const BREAKABLE_WALL_HEALTH: f32 = 60.0;
+
+/// wall covering the exit of a room that leads too a secret room,
+/// turns back into an opening when its health runs out
+#[derive(Debug, Component, Default, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct BreakableWall {
+    /// damage left before this wall breaks
+    pub health: f32,
+    /// exit covered by this wall, relative too dungeon
+    pub exit: IVec2,
+}
+
+/// secret room that already dropped its loot
+#[derive(Debug, Component, Default, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct SecretRoomLooted;
+
+/// covers the hidden exit of every secret room hallway with a `BreakableWall` once its dungeon is generated
+pub fn spawn_breakable_walls(
+    mut cmds: Commands,
+    mut generated_events: EventReader<DungeonGenerated>,
+    mut dungeons: Query<(&mut Dungeon, &Children)>,
+    hallways: Query<&HallWayBlueprint>,
+) {
+    for DungeonGenerated { entity } in generated_events.read() {
+        let Ok((mut dungeon, children)) = dungeons.get_mut(*entity) else {
+            continue;
+        };
+
+        let hidden_exits = children
+            .iter()
+            .filter_map(|f| hallways.get(*f).ok())
+            .filter_map(|f| f.hidden_exit);
+        for exit in hidden_exits {
+            // exits are relative too dungeon, same as sealed exit doors
+            let wall_position = exit.as_vec2() + Vec2::splat(TILE_SIZE / 2.0);
+            set_exit_tile(&mut dungeon, exit, TileType::Wall);
+
+            cmds.spawn((
+                Name::new("BreakableWall"),
+                BreakableWall {
+                    health: BREAKABLE_WALL_HEALTH,
+                    exit,
+                },
+                DamageQueue::default(),
+                Sprite {
+                    color: colors::DIM_GRAY.into(),
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..default()
+                },
+                Transform::from_translation(wall_position.extend(ACTOR_Z_INDEX - 1.0)),
+                RigidBody::Static,
+                Collider::rectangle(TILE_SIZE, TILE_SIZE),
+                AspenCollisionLayer::static_object(),
+            ))
+            .set_parent(*entity);
+        }
+    }
+}
+
+/// takes damage from breakable wall damage queues and opens walls with no health left
+pub fn damage_breakable_walls(
+    mut cmds: Commands,
+    mut walls: Query<(Entity, &mut BreakableWall, &mut DamageQueue, &Parent), Changed<DamageQueue>>,
+    mut dungeons: Query<&mut Dungeon>,
+) {
+    for (wall_entity, mut wall, mut damage_queue, dungeon) in &mut walls {
+        let damage: f32 = damage_queue.iter_queue().map(Damage::total).sum();
+        damage_queue.empty_queue();
+        wall.health -= damage;
+        if wall.health > 0.0 {
+            continue;
+        }
+
+        info!("breakable wall at {} destroyed", wall.exit);
+        cmds.entity(wall_entity).despawn_recursive();
+        if let Ok(mut dungeon) = dungeons.get_mut(dungeon.get()) {
+            set_exit_tile(&mut dungeon, wall.exit, TileType::RoomExit);
+        }
+    }
+}
+
+/// drops items from the `SecretLootTable` the first time the player enters a secret room,
+/// loot is rolled with the rng of the dungeon the room is in
+#[allow(clippy::too_many_arguments)]
+pub fn loot_secret_rooms(
+    mut cmds: Commands,
+    level_handles: Res<AspenLevelsetHandles>,
+    loot_tables: Res<Assets<SecretLootTable>>,
+    registry: Res<ActorRegistry>,
+    progress_manager: Query<&ProgressManager>,
+    player_query: Query<Entity, With<PlayerSelectedHero>>,
+    rooms: Query<(&RoomBlueprint, &Parent), Without<SecretRoomLooted>>,
+    mut dungeons: Query<&mut DungeonRng, With<Dungeon>>,
+    mut spawn_events: EventWriter<EventSpawnItem>,
+) {
+    let (Ok(progress_manager), Ok(player)) =
+        (progress_manager.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+    let Some(room_id) = progress_manager.current_room() else {
+        return;
+    };
+    let Ok((room, dungeon)) = rooms.get(room_id) else {
+        return;
+    };
+    if room.descriptor.rtype != RoomType::Secret {
+        return;
+    }
+    let Ok(mut rng) = dungeons.get_mut(dungeon.get()) else {
+        warn!("secret room {} is not inside a dungeon", room.name);
+        return;
+    };
+
+    let Some(loot_table) = loot_tables.get(&level_handles.secret_loot_table) else {
+        warn!("secret loot table is not loaded");
+        return;
+    };
+
+    info!("player found secret room {}", room.name);
+    cmds.entity(room_id).insert(SecretRoomLooted);
+    let loot: Vec<&(RegistryIdentifier, i32)> = loot_table
+        .items
+        .iter()
+        .filter(|(item, _)| {
+            let known = registry.items.get_item_type(item).is_some();
+            if !known {
+                warn!("secret loot table item {:?} is not in the registry", item);
+            }
+            known
+        })
+        .collect();
+    // loot is dropped where the player is standing so it always lands inside the room
+    for (item, amount) in loot.choose_multiple(&mut rng.0, loot_table.rolls) {
+        spawn_events.send(EventSpawnItem {
+            spawn_data: (item.clone(), *amount),
+            requester: player,
+        });
+    }
+}
+
+/// sets tile type of the tile under `exit` in `dungeon`s tile graph,
+/// keeps the minimap showing hidden exits as walls
+fn set_exit_tile(dungeon: &mut Dungeon, exit: IVec2, tile: TileType) {
+    let Dungeon {
+        settings,
+        tile_graph,
+        ..
+    } = dungeon;
+    let Some(coords) = tile_graph.get_cell_tile_coords(settings, world_cell(exit.as_vec2())) else {
+        warn!("exit {exit} is outside dungeon tile graph");
+        return;
+    };
+    tile_graph.set_tile_type(coords, tile);
+}
@@ -27,6 +27,7 @@ use bevy::prelude::*;
 
 pub use asha_macros::{on_enter, register_types};
 pub use bevy::color::palettes::css as colors;
+pub use game::game_world::dungeonator_v2::export::run_headless_export;
 pub use loading::config::*;
 
 /// application stages
@@ -8,7 +8,11 @@ use bevy::{
 use bevy_asset_loader::prelude::AssetCollection;
 use bevy_ecs_ldtk::prelude::LdtkProject;
 
-use crate::loading::custom_assets::actor_definitions::{CharacterDefinition, ItemDefinition};
+use crate::loading::custom_assets::{
+    actor_definitions::{CharacterDefinition, ItemDefinition},
+    floor_definitions::FloorTable,
+    loot_definitions::SecretLootTable,
+};
 
 /// ui textures
 #[derive(AssetCollection, Resource, Clone, Debug, Reflect)]
@@ -114,6 +118,12 @@ pub struct AspenLevelsetHandles {
     /// dungeons
     #[asset(key = "default_levels")]
     pub default_levels: Handle<LdtkProject>,
+    /// floors for dungeon runs
+    #[asset(key = "floor_table")]
+    pub floor_table: Handle<FloorTable>,
+    /// items secret rooms drop
+    #[asset(key = "secret_loot_table")]
+    pub secret_loot_table: Handle<SecretLootTable>,
 }
 
 /// miscellaneous texture asset handles for tiles
@@ -109,6 +109,10 @@ pub struct GeneralSettings {
     /// camera zoom
     #[inspector(min = 0.0, max = 150.0)]
     pub camera_zoom: f32,
+    /// seed used for dungeon generation,
+    /// a random seed is chosen for each dungeon if this is `None`
+    #[serde(default)]
+    pub dungeon_seed: Option<u64>,
     /// master game difficulty,
     /// configures the actual difficulty settings
     /// value ranging from 1-4, 1 being easiest, 4 being hardest
@@ -200,6 +204,7 @@ impl Default for GeneralSettings {
     fn default() -> Self {
         Self {
             camera_zoom: 5.5,
+            dungeon_seed: None,
             game_difficulty: GameDifficulty::Custom(DifficultySettings::default()),
             enable_debug: cfg!(feature = "develop"),
             enable_touch_controls: cfg!(target_os = "android") || cfg!(target_os = "ios"),
@@ -0,0 +1,107 @@
+use bevy::{asset::ReflectAsset, prelude::*};
+use bevy_common_assets::ron::RonAssetPlugin;
+
+use crate::{
+    game::game_world::dungeonator_v2::{
+        components::{HallwayWidths, RoomBiome, RoomDistribution, RoomLevel, RoomType},
+        hallways::DEFAULT_HALLWAY_TILESET,
+        room_graph::rules::PlacementRule,
+    },
+    loading::registry::RegistryIdentifier,
+};
+
+/// plugin for dungeon floor table assets
+pub struct FloorAssetPlugin;
+
+impl Plugin for FloorAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_reflect::<FloorTable>()
+            .add_plugins(RonAssetPlugin::<FloorTable>::new(&["floors.ron"]));
+    }
+}
+
+/// every floor of a dungeon run, in the order they are played
+#[derive(Debug, Asset, Reflect, serde::Deserialize, serde::Serialize)]
+#[reflect(Asset)]
+pub struct FloorTable {
+    /// floor definitions, first entry is the first floor
+    pub floors: Vec<FloorDefinition>,
+}
+
+/// settings used too generate a single dungeon floor
+#[derive(Debug, Clone, Reflect, serde::Deserialize, serde::Serialize)]
+pub struct FloorDefinition {
+    /// how wide/tall this floor should be in tiles
+    pub size: UVec2,
+    /// border around outside of dungeon in tiles
+    pub border: u32,
+    /// minimum space between rooms in tiles
+    #[serde(default = "FloorDefinition::default_tiles_between_rooms")]
+    pub tiles_between_rooms: u32,
+    /// amount of rooms inside this floor
+    pub distribution: RoomDistribution,
+    /// percentage of paths between rooms that are chosen to loop
+    pub hallway_loop_chance: f32,
+    /// how wide this floors hallways are built
+    #[serde(default)]
+    pub hallway_widths: HallwayWidths,
+    /// room levels that can be placed on this floor
+    pub room_levels: Vec<RoomLevel>,
+    /// theme of this floors rooms
+    #[serde(default)]
+    pub biome: RoomBiome,
+    /// identifier of the ldtk tileset this floors hallways are built from
+    #[serde(default = "FloorDefinition::default_hallway_tileset")]
+    pub hallway_tileset: String,
+    /// bosses that can be spawned in this floors end room,
+    /// empty keeps the bosses defined in the room asset
+    #[serde(default)]
+    pub boss_pool: Vec<RegistryIdentifier>,
+    /// rules this floors room graph must follow
+    #[serde(default = "PlacementRule::default_rules")]
+    pub placement_rules: Vec<PlacementRule>,
+}
+
+impl FloorDefinition {
+    /// space between rooms used when a floor does not set `tiles_between_rooms`
+    pub const fn default_tiles_between_rooms() -> u32 {
+        4
+    }
+
+    /// hallway tileset used when a floor does not set `hallway_tileset`
+    pub fn default_hallway_tileset() -> String {
+        DEFAULT_HALLWAY_TILESET.to_string()
+    }
+}
+
+impl Default for FloorDefinition {
+    fn default() -> Self {
+        Self {
+            size: UVec2 { x: 152, y: 152 },
+            border: 4,
+            tiles_between_rooms: Self::default_tiles_between_rooms(),
+            distribution: RoomDistribution {
+                small_short: 3,
+                small_long: 2,
+                medium_short: 1,
+                medium_long: 1,
+                large_short: 0,
+                large_long: 0,
+                huge_short: 0,
+                huge_long: 0,
+                special: 2,
+                secret: 1,
+            },
+            hallway_loop_chance: 0.08,
+            hallway_widths: HallwayWidths {
+                default: 2,
+                room_types: vec![(RoomType::DungeonEnd, 3)],
+            },
+            room_levels: vec![RoomLevel::Level1],
+            biome: RoomBiome::Crypt,
+            hallway_tileset: Self::default_hallway_tileset(),
+            boss_pool: Vec::new(),
+            placement_rules: PlacementRule::default_rules(),
+        }
+    }
+}
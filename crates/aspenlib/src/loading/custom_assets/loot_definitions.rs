@@ -0,0 +1,32 @@
+use bevy::{asset::ReflectAsset, prelude::*};
+use bevy_common_assets::ron::RonAssetPlugin;
+
+use crate::loading::registry::RegistryIdentifier;
+
+/// plugin for loot table assets
+pub struct LootAssetPlugin;
+
+impl Plugin for LootAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_reflect::<SecretLootTable>()
+            .add_plugins(RonAssetPlugin::<SecretLootTable>::new(&["loot.ron"]));
+    }
+}
+
+/// items secret rooms can drop when the player first enters them
+#[derive(Debug, Asset, Reflect, serde::Deserialize, serde::Serialize)]
+#[reflect(Asset)]
+pub struct SecretLootTable {
+    /// item ids and how many of each too spawn
+    pub items: Vec<(RegistryIdentifier, i32)>,
+    /// how many different entrys from `items` each secret room drops
+    #[serde(default = "SecretLootTable::default_rolls")]
+    pub rolls: usize,
+}
+
+impl SecretLootTable {
+    /// rolls used when a table does not set `rolls`
+    pub const fn default_rolls() -> usize {
+        1
+    }
+}
@@ -1,14 +1,21 @@
-use crate::loading::custom_assets::actor_definitions::ActorAssetPlugin;
+use crate::loading::custom_assets::{
+    actor_definitions::ActorAssetPlugin, floor_definitions::FloorAssetPlugin,
+    loot_definitions::LootAssetPlugin,
+};
 use bevy::app::Plugin;
 
 /// holds aspen halls custom actor asset plugins
 pub mod actor_definitions;
+/// dungeon floor table assets
+pub mod floor_definitions;
+/// loot table assets
+pub mod loot_definitions;
 
 /// handles initialization of all custom assets
 pub struct AspenCustomAssetsPlugin;
 
 impl Plugin for AspenCustomAssetsPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_plugins(ActorAssetPlugin);
+        app.add_plugins((ActorAssetPlugin, FloorAssetPlugin, LootAssetPlugin));
     }
 }
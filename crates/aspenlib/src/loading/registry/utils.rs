@@ -12,7 +12,7 @@ use crate::{
     bundles::{Aspen2dRenderBundle, CharacterBundle, WeaponBundle},
     game::{
         attributes_stats::{Attributes, CharacterStatBundle, EquipmentStats},
-        characters::components::CharacterMoveState,
+        characters::components::{CharacterMoveState, HazardContacts},
         items::weapons::components::{AttackDamage, WeaponDescriptor, WeaponHolder},
     },
     loading::{
@@ -42,6 +42,7 @@ pub fn build_character_bundles(
             actor_type: character_def.character_type.as_charactertype(),
             stats: CharacterStatBundle::from_attrs(character_def.actor.stats),
             move_state: CharacterMoveState::DEFAULT,
+            hazards: HazardContacts::default(),
             controller: character_def.character_type.get_ai(),
             render: Aspen2dRenderBundle {
                 handle: AseSpriteAnimation {
@@ -9,12 +9,12 @@ use aspenlib::{
     VolumeConfig, WindowSettings,
 };
 use bevy::{log::info, math::Vec2};
-use std::path::Path;
+use std::{path::Path, process::ExitCode};
 
 /// this translates too same folder as executable
 pub const APP_SETTINGS_PATH: &str = "./config.toml";
 
-fn main() {
+fn main() -> ExitCode {
     info!("Starting launcher: Native");
     // check AppDir for config file, if no cfg, first run, guess settings based on hardware
     // otherwise load app settings from config file
@@ -25,9 +25,15 @@ fn main() {
             .support("- Open a support request on github")
     );
 
+    // `export-layout` writes dungeon layouts too disk without opening a window
+    if std::env::args().nth(1).as_deref() == Some("export-layout") {
+        return aspenlib::run_headless_export(std::env::args().skip(1));
+    }
+
     let cfg_file: ConfigFile = load_settings();
 
     aspenlib::start_app(cfg_file).run();
+    ExitCode::SUCCESS
 }
 
 /// loads app settings from `consts::APP_SETTINGS_PATH` and returns a boxed config file
@@ -98,6 +104,7 @@ pub fn load_settings() -> ConfigFile {
                     },
                     enable_touch_controls: cfg.general_settings.enable_touch_controls,
                     camera_zoom: cfg.general_settings.camera_zoom,
+                    dungeon_seed: cfg.general_settings.dungeon_seed,
                     game_difficulty: cfg.general_settings.game_difficulty,
                 },
                 render_settings: RenderSettings {
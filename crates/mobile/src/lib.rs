@@ -44,6 +44,7 @@ fn main() {
             },
             enable_touch_controls: true,
             camera_zoom: 5.5,
+            dungeon_seed: None,
             game_difficulty: GameDifficulty::Medium,
         },
         render_settings: RenderSettings { msaa: false },
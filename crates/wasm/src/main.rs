@@ -59,6 +59,7 @@ fn main() {
             enable_debug: cfg!(feature = "develop"),
             enable_touch_controls: false,
             camera_zoom: 3.5,
+            dungeon_seed: None,
             game_difficulty: GameDifficulty::Easy,
         },
         render_settings: RenderSettings { msaa: false },